@@ -107,13 +107,6 @@ test_file_size() {
     local original_hash=$(calculate_hash "$original_file")
     print_status "Original file hash: ${original_hash:0:16}..."
     
-    # Special handling for empty files (Huffman compression may not handle these)
-    if [ "$size" -eq 0 ]; then
-        print_warning "Skipping compression test for empty file (may not be supported)"
-        print_success "Empty file test completed for $test_name"
-        return 0
-    fi
-    
     # Compress the file
     local compressed_file="$TEMP_DIR/test_$test_name.huf"
     print_status "Compressing..."
@@ -212,6 +205,7 @@ run_tests() {
     
     # Test file sizes (in bytes)
     local test_sizes=(
+        0          # Empty file
         1          # Single byte
         10         # Very small
         50         # Small
@@ -227,7 +221,7 @@ run_tests() {
     
     for size in "${test_sizes[@]}"; do
         if test_file_size "$size"; then
-            ((passed++))
+            passed=$((passed + 1))
         fi
         echo
     done
@@ -0,0 +1,257 @@
+use crate::block_format::BLOCK_TYPE_ADAPTIVE;
+use crate::compression::CompressionOptions;
+use crate::entropy_coder::EntropyCoder;
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::file_header::METHOD_ADAPTIVE_HUFFMAN;
+use crate::frequency_map::ByteFrequencyMap;
+use crate::input_bit_stream::InputBitStream;
+use crate::output_bit_stream::OutputBitStream;
+use std::io::{Read, Write};
+
+// Marks a missing parent or an unseen symbol
+const NONE: usize = usize::MAX;
+
+// Bits used to spell out a symbol the first time it appears
+const LITERAL_BITS: u32 = 8;
+
+// A node of the adaptive tree; leaves hold a symbol, internal nodes hold two children
+#[derive(Debug, Clone)]
+struct AdaptiveNode {
+    weight: u64,
+    parent: usize,
+    // Left (bit 0) and right (bit 1) child, None for a leaf
+    children: Option<[usize; 2]>,
+    symbol: Option<u8>,
+}
+
+// Adaptive Huffman model following the FGK algorithm (Faller, Gallager and Knuth)
+// Encoder and decoder start from the same tree holding only the NYT ("not yet transmitted")
+// leaf and update it identically after every symbol, so no tree is ever stored
+// A symbol seen before is sent as the path to its leaf; a new symbol is sent as the path to
+// the NYT leaf followed by its 8 bits, after which NYT splits into a new NYT and the symbol
+// Nodes are ranked so that weights never increase from the root down the ranking (the
+// sibling property); an update swaps each node on the path with the highest-ranked node of
+// equal weight before incrementing it, which keeps the tree a Huffman tree for the counts
+#[derive(Debug, Clone)]
+pub struct AdaptiveHuffmanModel {
+    nodes: Vec<AdaptiveNode>,
+    // Node ids by rank; rank 0 is the root and new nodes take the lowest ranks
+    order: Vec<usize>,
+    // Rank of every node id
+    rank: Vec<usize>,
+    // Leaf of every symbol seen so far, NONE for the others
+    leaves: [usize; 256],
+    nyt: usize,
+    // Scratch space for the path of the symbol being encoded, leaf first
+    path: Vec<u8>,
+}
+
+impl Default for AdaptiveHuffmanModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdaptiveHuffmanModel {
+    // Creates the initial model: a single NYT leaf that is also the root
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![AdaptiveNode {
+                weight: 0,
+                parent: NONE,
+                children: None,
+                symbol: None,
+            }],
+            order: vec![0],
+            rank: vec![0],
+            leaves: [NONE; 256],
+            nyt: 0,
+            path: Vec::new(),
+        }
+    }
+
+    // Writes the bits leading from the root to the given node
+    // The path is collected walking up from the node, so it is written back to front
+    fn write_path<W: Write>(
+        &mut self,
+        node: usize,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        self.path.clear();
+        let mut child = node;
+        while self.nodes[child].parent != NONE {
+            let parent = self.nodes[child].parent;
+            let is_right = self.nodes[parent].children.is_some_and(|c| c[1] == child);
+            self.path.push(is_right as u8);
+            child = parent;
+        }
+        self.path.reverse();
+
+        self.path.chunks(u64::BITS as usize).try_for_each(|chunk| {
+            let bits = chunk.iter().fold(0u64, |bits, &bit| bits << 1 | bit as u64);
+            bit_stream.write_bits(bits, chunk.len() as u32)
+        })
+    }
+
+    // Records one more occurrence of the symbol
+    // Step 1: For a new symbol, split the NYT leaf into a new NYT and a leaf for the symbol
+    // Step 2: Walk from the symbol's leaf to the root; at each node, swap it with the
+    //         highest-ranked node of the same weight (unless that is its parent), then
+    //         increment its weight
+    fn update(&mut self, symbol: u8) {
+        let mut node = match self.leaves[symbol as usize] {
+            NONE => self.split_nyt(symbol),
+            leaf => leaf,
+        };
+
+        loop {
+            let leader = self.block_leader(node);
+            if leader != node && leader != self.nodes[node].parent {
+                self.swap(node, leader);
+            }
+            self.nodes[node].weight += 1;
+            match self.nodes[node].parent {
+                NONE => break,
+                parent => node = parent,
+            }
+        }
+    }
+
+    // Turns the NYT leaf into an internal node whose children are a new NYT and a new leaf
+    // for the symbol, both ranked just below it; returns the new leaf
+    fn split_nyt(&mut self, symbol: u8) -> usize {
+        let parent = self.nyt;
+        let leaf = self.push_node(parent, Some(symbol));
+        let nyt = self.push_node(parent, None);
+        self.nodes[parent].children = Some([nyt, leaf]);
+        self.leaves[symbol as usize] = leaf;
+        self.nyt = nyt;
+        leaf
+    }
+
+    // Adds a leaf of weight zero below every existing node in the ranking
+    fn push_node(&mut self, parent: usize, symbol: Option<u8>) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(AdaptiveNode {
+            weight: 0,
+            parent,
+            children: None,
+            symbol,
+        });
+        self.rank.push(self.order.len());
+        self.order.push(id);
+        id
+    }
+
+    // Finds the highest-ranked node with the same weight as the given node
+    // Weights never increase along the ranking, so the first such rank is found by bisection
+    fn block_leader(&self, node: usize) -> usize {
+        let weight = self.nodes[node].weight;
+        let ranked = &self.order[..=self.rank[node]];
+        ranked[ranked.partition_point(|&other| self.nodes[other].weight > weight)]
+    }
+
+    // Exchanges two subtrees: each takes the other's place under its parent and in the ranking
+    fn swap(&mut self, a: usize, b: usize) {
+        let (parent_a, parent_b) = (self.nodes[a].parent, self.nodes[b].parent);
+        if parent_a == parent_b {
+            self.children_mut(parent_a).swap(0, 1);
+        } else {
+            self.replace_child(parent_a, a, b);
+            self.replace_child(parent_b, b, a);
+            self.nodes[a].parent = parent_b;
+            self.nodes[b].parent = parent_a;
+        }
+
+        let (rank_a, rank_b) = (self.rank[a], self.rank[b]);
+        self.order.swap(rank_a, rank_b);
+        self.rank.swap(a, b);
+    }
+
+    // Points the parent's link to `from` at `to`
+    fn replace_child(&mut self, parent: usize, from: usize, to: usize) {
+        let children = self.children_mut(parent);
+        let slot = (children[1] == from) as usize;
+        children[slot] = to;
+    }
+
+    // Returns the children of a node that is known to be internal
+    fn children_mut(&mut self, node: usize) -> &mut [usize; 2] {
+        self.nodes[node]
+            .children
+            .as_mut()
+            .expect("Only internal nodes have children")
+    }
+}
+
+impl EntropyCoder for AdaptiveHuffmanModel {
+    const METHOD: u8 = METHOD_ADAPTIVE_HUFFMAN;
+    const BLOCK_TYPE: u8 = BLOCK_TYPE_ADAPTIVE;
+
+    // The model learns the counts as it goes, so they are not needed up front
+    fn from_frequencies(
+        frequency_map: &ByteFrequencyMap,
+        _options: &CompressionOptions,
+    ) -> Option<Self> {
+        (!frequency_map.is_empty()).then(Self::new)
+    }
+
+    // Skips the counting pass: every block starts from the same empty tree
+    fn for_block(block: &[u8], _options: &CompressionOptions) -> std::io::Result<Option<Self>> {
+        Ok((!block.is_empty()).then(Self::new))
+    }
+
+    // Nothing is stored: the decoder starts from the same empty tree
+    fn serialize_model<W: Write>(
+        &self,
+        _bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn deserialize_model<R: Read>(_bit_stream: &mut InputBitStream<R>) -> HuffmanResult<Self> {
+        Ok(Self::new())
+    }
+
+    // Writes the code of one symbol with the current tree, then updates the tree
+    fn encode_symbol<W: Write>(
+        &mut self,
+        symbol: u8,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        let leaf = self.leaves[symbol as usize];
+        let is_new = leaf == NONE;
+        self.write_path(if is_new { self.nyt } else { leaf }, bit_stream)?;
+        if is_new {
+            bit_stream.write_bits(symbol as u64, LITERAL_BITS)?;
+        }
+        self.update(symbol);
+        Ok(())
+    }
+
+    // Reads the code of one symbol with the current tree, then updates the tree
+    // Step 1: Follow one bit per level from the root down to a leaf
+    // Step 2: At the NYT leaf, read the new symbol's 8 literal bits
+    // Step 3: Update the tree exactly as the encoder did
+    fn decode_symbol<R: Read>(&mut self, bit_stream: &mut InputBitStream<R>) -> HuffmanResult<u8> {
+        let mut node = self.order[0];
+        while let Some(children) = self.nodes[node].children {
+            node = children[bit_stream.read_bit()? as usize];
+        }
+
+        let symbol = match self.nodes[node].symbol {
+            Some(symbol) => symbol,
+            None => {
+                let symbol = bit_stream.read_bits(LITERAL_BITS)? as u8;
+                if self.leaves[symbol as usize] != NONE {
+                    return Err(HuffmanError::CorruptBlock(format!(
+                        "Symbol {symbol} is introduced a second time"
+                    )));
+                }
+                symbol
+            }
+        };
+        self.update(symbol);
+        Ok(symbol)
+    }
+}
@@ -0,0 +1,155 @@
+use crate::coding_method::{coding_method, CODING_METHODS};
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::file_header::METHOD_HUFFMAN;
+use std::io::{Read, Write};
+
+pub const BLOCK_TYPE_END: u8 = 0;
+pub const BLOCK_TYPE_HUFFMAN: u8 = 1;
+pub const BLOCK_TYPE_STORED: u8 = 2;
+pub const BLOCK_TYPE_CANONICAL: u8 = 3;
+pub const BLOCK_TYPE_ADAPTIVE: u8 = 4;
+pub const BLOCK_TYPE_RANGE: u8 = 5;
+pub const BLOCK_TYPE_TANS: u8 = 6;
+pub const BLOCK_TYPE_CONTEXT: u8 = 7;
+
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+
+// Bytes taken by the header of a data block; the end marker is a single type byte
+pub const BLOCK_HEADER_SIZE: u64 = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub block_type: u8,
+    pub original_length: u32,
+    pub payload_length: u32,
+}
+
+impl BlockHeader {
+    // Creates the marker that terminates the sequence of blocks
+    pub fn end() -> Self {
+        Self {
+            block_type: BLOCK_TYPE_END,
+            original_length: 0,
+            payload_length: 0,
+        }
+    }
+
+    // Returns true if this is the end-of-stream marker
+    pub fn is_end(&self) -> bool {
+        self.block_type == BLOCK_TYPE_END
+    }
+
+    // Returns how many bytes the header and its payload take in the stream
+    pub fn encoded_length(&self) -> u64 {
+        match self.is_end() {
+            true => 1,
+            false => BLOCK_HEADER_SIZE + self.payload_length as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamTrailer {
+    pub original_length: u64,
+    pub checksum: Option<u32>,
+}
+
+// Writes the header that precedes every block's payload
+// Layout: 1 block type byte, then (except for the end marker) the 4-byte little-endian
+// original length and the 4-byte little-endian payload length
+pub fn write_block_header<W: Write>(writer: &mut W, header: &BlockHeader) -> std::io::Result<()> {
+    writer.write_all(&[header.block_type])?;
+    if header.is_end() {
+        return Ok(());
+    }
+    writer.write_all(&header.original_length.to_le_bytes())?;
+    writer.write_all(&header.payload_length.to_le_bytes())
+}
+
+// Reads a block header written by write_block_header
+// Rejects block types this version does not know how to decode: besides stored and legacy
+// tree blocks, only the block types of known coding methods
+pub fn read_block_header<R: Read>(reader: &mut R) -> HuffmanResult<BlockHeader> {
+    let mut block_type = [0u8; 1];
+    reader.read_exact(&mut block_type)?;
+    match block_type[0] {
+        BLOCK_TYPE_END => Ok(BlockHeader::end()),
+        block_type
+            if block_type == BLOCK_TYPE_HUFFMAN
+                || block_type == BLOCK_TYPE_STORED
+                || CODING_METHODS
+                    .iter()
+                    .any(|method| method.block_type == block_type) =>
+        {
+            let mut lengths = [0u8; 8];
+            reader.read_exact(&mut lengths)?;
+            let [a, b, c, d, e, f, g, h] = lengths;
+            Ok(BlockHeader {
+                block_type,
+                original_length: u32::from_le_bytes([a, b, c, d]),
+                payload_length: u32::from_le_bytes([e, f, g, h]),
+            })
+        }
+        unknown => Err(HuffmanError::UnknownBlockType(unknown)),
+    }
+}
+
+// Returns true if blocks of this type belong in a stream coded with the given method
+// Stored blocks belong to every method, since any coder may meet data it cannot shrink, and
+// legacy tree blocks to static Huffman streams; otherwise only the method's own block type
+pub fn block_type_fits_method(block_type: u8, method: u8) -> bool {
+    match (method, block_type) {
+        (_, BLOCK_TYPE_STORED) | (METHOD_HUFFMAN, BLOCK_TYPE_HUFFMAN) => true,
+        _ => coding_method(method).is_some_and(|coding| coding.block_type == block_type),
+    }
+}
+
+// Reads the payload that follows a block header
+// The payload is read through take() rather than into a buffer of the declared size,
+// so a forged length costs no more memory than the input actually holds
+pub fn read_block_payload<R: Read>(reader: &mut R, header: &BlockHeader) -> HuffmanResult<Vec<u8>> {
+    let mut payload = Vec::new();
+    reader
+        .by_ref()
+        .take(header.payload_length as u64)
+        .read_to_end(&mut payload)?;
+    if payload.len() < header.payload_length as usize {
+        return Err(HuffmanError::TruncatedStream);
+    }
+    Ok(payload)
+}
+
+// Writes the trailer that follows the end marker
+// Layout: 8-byte little-endian total original length, then the 4-byte CRC-32 if present
+pub fn write_stream_trailer<W: Write>(
+    writer: &mut W,
+    trailer: &StreamTrailer,
+) -> std::io::Result<()> {
+    writer.write_all(&trailer.original_length.to_le_bytes())?;
+    trailer
+        .checksum
+        .map_or(Ok(()), |checksum| writer.write_all(&checksum.to_le_bytes()))
+}
+
+// Reads the trailer written by write_stream_trailer
+// Whether a checksum is present is recorded in the file header, so the caller passes it in
+pub fn read_stream_trailer<R: Read>(
+    reader: &mut R,
+    has_checksum: bool,
+) -> HuffmanResult<StreamTrailer> {
+    let mut length_bytes = [0u8; 8];
+    reader.read_exact(&mut length_bytes)?;
+
+    let checksum = if has_checksum {
+        let mut checksum_bytes = [0u8; 4];
+        reader.read_exact(&mut checksum_bytes)?;
+        Some(u32::from_le_bytes(checksum_bytes))
+    } else {
+        None
+    };
+
+    Ok(StreamTrailer {
+        original_length: u64::from_le_bytes(length_bytes),
+        checksum,
+    })
+}
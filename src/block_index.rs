@@ -0,0 +1,117 @@
+use crate::block_format::BLOCK_HEADER_SIZE;
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::file_header::FILE_HEADER_SIZE;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+// Bytes taken by one index entry: the block's offset and its original length
+pub const INDEX_ENTRY_SIZE: u64 = 12;
+// Bytes taken by the entry count that ends the index
+pub const INDEX_COUNT_SIZE: u64 = 4;
+
+// Where one block starts in the compressed stream and how many bytes it decodes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockIndexEntry {
+    // Position of the block header, counted from the first byte of the file header
+    pub offset: u64,
+    pub original_length: u32,
+}
+
+// Writes the block index that follows the trailer when FLAG_BLOCK_INDEX is set
+// Layout: for every block, its 8-byte little-endian offset and 4-byte little-endian original
+// length, then the 4-byte little-endian number of entries
+// The count comes last so that a reader able to seek finds the index from the end of the file
+pub fn write_block_index<W: Write>(
+    writer: &mut W,
+    entries: &[BlockIndexEntry],
+) -> std::io::Result<()> {
+    entries.iter().try_for_each(|entry| {
+        writer.write_all(&entry.offset.to_le_bytes())?;
+        writer.write_all(&entry.original_length.to_le_bytes())
+    })?;
+    writer.write_all(&(entries.len() as u32).to_le_bytes())
+}
+
+// Reads one entry written by write_block_index
+fn read_entry<R: Read>(reader: &mut R) -> HuffmanResult<BlockIndexEntry> {
+    let mut entry = [0u8; INDEX_ENTRY_SIZE as usize];
+    reader.read_exact(&mut entry)?;
+    let [a, b, c, d, e, f, g, h, i, j, k, l] = entry;
+    Ok(BlockIndexEntry {
+        offset: u64::from_le_bytes([a, b, c, d, e, f, g, h]),
+        original_length: u32::from_le_bytes([i, j, k, l]),
+    })
+}
+
+// Reads the count that ends the index
+fn read_count<R: Read>(reader: &mut R) -> HuffmanResult<u32> {
+    let mut count = [0u8; INDEX_COUNT_SIZE as usize];
+    reader.read_exact(&mut count)?;
+    Ok(u32::from_le_bytes(count))
+}
+
+// Reads an index whose length the caller already knows, as a sequential decoder does after
+// counting the blocks it decoded; fails if the stored count disagrees
+pub fn read_block_index_entries<R: Read>(
+    reader: &mut R,
+    count: usize,
+) -> HuffmanResult<Vec<BlockIndexEntry>> {
+    let entries = (0..count)
+        .map(|_| read_entry(reader))
+        .collect::<HuffmanResult<Vec<_>>>()?;
+    let stored_count = read_count(reader)?;
+    if stored_count as usize != count {
+        return Err(HuffmanError::CorruptIndex(format!(
+            "Index lists {stored_count} blocks but the stream holds {count}"
+        )));
+    }
+    Ok(entries)
+}
+
+// Reads the index from the end of a seekable stream, leaving the reader positioned after it
+// Step 1: Read the entry count from the last 4 bytes
+// Step 2: Check that that many entries fit between the file header and the count
+// Step 3: Read the entries and check that the blocks follow one another from the file header
+// Offsets are only checked to be ordered here; the decoder checks each against the block it finds
+// `start` is the reader position of the file header; every bound is measured from it, like the
+// offsets in the index, so a stream that does not begin the reader is read the same way
+pub fn read_block_index<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+) -> HuffmanResult<Vec<BlockIndexEntry>> {
+    let stream_length = reader
+        .seek(SeekFrom::End(0))?
+        .checked_sub(start)
+        .ok_or(HuffmanError::TruncatedStream)?;
+    if stream_length < FILE_HEADER_SIZE + INDEX_COUNT_SIZE {
+        return Err(HuffmanError::TruncatedStream);
+    }
+    reader.seek(SeekFrom::End(-(INDEX_COUNT_SIZE as i64)))?;
+    let count = read_count(reader)? as u64;
+
+    let index_length = count * INDEX_ENTRY_SIZE + INDEX_COUNT_SIZE;
+    if index_length > stream_length - FILE_HEADER_SIZE {
+        return Err(HuffmanError::CorruptIndex(format!(
+            "Index of {count} blocks does not fit in a {stream_length}-byte stream"
+        )));
+    }
+    let index_start = stream_length - index_length;
+    reader.seek(SeekFrom::Start(start + index_start))?;
+    let entries = read_block_index_entries(reader, count as usize)?;
+
+    let first_offset_is_valid = entries
+        .first()
+        .is_none_or(|entry| entry.offset == FILE_HEADER_SIZE);
+    let offsets_are_ordered = entries
+        .windows(2)
+        .all(|pair| pair[1].offset >= pair[0].offset + BLOCK_HEADER_SIZE);
+    let last_offset_is_valid = entries
+        .last()
+        .is_none_or(|entry| entry.offset + BLOCK_HEADER_SIZE <= index_start);
+    if !(first_offset_is_valid && offsets_are_ordered && last_offset_is_valid) {
+        return Err(HuffmanError::CorruptIndex(
+            "Block offsets are out of order or outside the stream".to_string(),
+        ));
+    }
+
+    Ok(entries)
+}
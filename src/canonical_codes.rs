@@ -0,0 +1,234 @@
+use crate::code_extraction::{HuffmanCode, HuffmanCodeTable};
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::frequency_map::ByteFrequencyMap;
+use crate::tree_construction::HuffmanNode;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+pub const SYMBOL_COUNT: usize = 256;
+
+// Longest code a canonical code table may contain
+// Codes are computed in a u64; a Huffman tree deeper than 64 levels would need
+// Fibonacci-like frequencies summing to more than any block can hold (blocks are < 4 GiB),
+// and codes built from larger caller-supplied counts are limited to this length instead
+pub const MAX_CANONICAL_CODE_LENGTH: u8 = 64;
+
+// Code length of every byte value, indexed by symbol; zero means the symbol is absent
+pub type CodeLengths = [u8; SYMBOL_COUNT];
+
+// Records the depth of every leaf in the tree as that symbol's code length
+// A tree made of a single leaf gets length 1, which only marks its symbol as present in the
+// serialized table; HuffmanCoder writes no bits for such a tree
+pub fn code_lengths_from_tree(tree: &HuffmanNode) -> CodeLengths {
+    let mut lengths = [0u8; SYMBOL_COUNT];
+    if let Some(symbol) = tree.symbol() {
+        lengths[symbol as usize] = 1;
+    } else {
+        collect_leaf_depths(tree, 0, &mut lengths);
+    }
+    lengths
+}
+
+// Computes the same code lengths as code_lengths_from_tree(&build_huffman_tree(..)) without
+// building the tree: nodes are merged in the same order (lowest frequency, then lowest
+// symbol), but only each node's parent is recorded
+// Step 1: Merge the two lightest nodes until one is left, noting the parent of both
+// Step 2: Every node lies one level below its parent, and parents come after their children
+// Returns None for an empty map
+pub fn huffman_code_lengths(frequency_map: &ByteFrequencyMap) -> Option<CodeLengths> {
+    let symbols: Vec<u8> = frequency_map.keys().copied().collect();
+    let mut lengths = [0u8; SYMBOL_COUNT];
+    match symbols.as_slice() {
+        [] => return None,
+        [symbol] => {
+            lengths[*symbol as usize] = 1;
+            return Some(lengths);
+        }
+        _ => {}
+    }
+
+    let mut heap: BinaryHeap<Reverse<(usize, u8, usize)>> = symbols
+        .iter()
+        .enumerate()
+        .map(|(node, &symbol)| Reverse((frequency_map[&symbol], symbol, node)))
+        .collect();
+    let mut parents = vec![0; symbols.len() * 2 - 1];
+    (symbols.len()..parents.len()).for_each(|parent| {
+        let Reverse((frequency1, lowest1, node1)) = heap.pop().expect("Heap has 2 nodes");
+        let Reverse((frequency2, lowest2, node2)) = heap.pop().expect("Heap has 2 nodes");
+        parents[node1] = parent;
+        parents[node2] = parent;
+        heap.push(Reverse((
+            frequency1 + frequency2,
+            lowest1.min(lowest2),
+            parent,
+        )));
+    });
+
+    let mut depths = vec![0u8; parents.len()];
+    (0..parents.len() - 1)
+        .rev()
+        .for_each(|node| depths[node] = depths[parents[node]] + 1);
+    symbols
+        .iter()
+        .zip(&depths)
+        .for_each(|(&symbol, &depth)| lengths[symbol as usize] = depth);
+    Some(lengths)
+}
+
+// Walks the tree depth-first and stores each leaf's depth in the lengths table
+fn collect_leaf_depths(node: &HuffmanNode, depth: u8, lengths: &mut CodeLengths) {
+    match node.symbol() {
+        Some(symbol) => lengths[symbol as usize] = depth,
+        None => {
+            [node.left_child(), node.right_child()]
+                .into_iter()
+                .flatten()
+                .for_each(|child| collect_leaf_depths(child, depth + 1, lengths));
+        }
+    }
+}
+
+// Assigns canonical code values from code lengths alone
+// Symbols are ordered by (length, symbol value); each code is the previous one plus one,
+// shifted left whenever the length grows. Any two implementations that agree on the
+// lengths therefore agree on every code, which is what lets the header store only lengths
+// Returns (symbol, code value, code length) triples in canonical order
+pub fn canonical_code_values(lengths: &CodeLengths) -> Vec<(u8, u64, u8)> {
+    let mut symbols: Vec<(u8, u8)> = lengths
+        .iter()
+        .enumerate()
+        .filter(|(_, &length)| length > 0)
+        .map(|(symbol, &length)| (symbol as u8, length))
+        .collect();
+    symbols.sort_by_key(|&(symbol, length)| (length, symbol));
+
+    let mut next_code = 0u64;
+    let mut previous_length = symbols.first().map_or(0, |&(_, length)| length);
+
+    symbols
+        .into_iter()
+        .map(|(symbol, length)| {
+            next_code <<= length - previous_length;
+            previous_length = length;
+            let code = next_code;
+            next_code = next_code.wrapping_add(1);
+            (symbol, code, length)
+        })
+        .collect()
+}
+
+// Builds the symbol-to-code lookup table used by the encoder from canonical code values
+pub fn assign_canonical_codes(lengths: &CodeLengths) -> HuffmanCodeTable {
+    canonical_code_values(lengths)
+        .into_iter()
+        .map(|(symbol, code, length)| (symbol, HuffmanCode::new(code, length)))
+        .collect()
+}
+
+// Creates the error reported for code length tables that cannot be decoded
+fn invalid_code_lengths(message: String) -> HuffmanError {
+    HuffmanError::CorruptTree(message)
+}
+
+// Checks that the lengths describe a usable prefix code
+// Every length must fit in MAX_CANONICAL_CODE_LENGTH and the Kraft sum must be exactly one,
+// except for the single-symbol case, whose length of 1 only marks the symbol as present;
+// HuffmanCoder writes no bits for such a table
+fn validate_code_lengths(lengths: &CodeLengths) -> HuffmanResult<()> {
+    if let Some(&length) = lengths
+        .iter()
+        .find(|&&length| length > MAX_CANONICAL_CODE_LENGTH)
+    {
+        return Err(invalid_code_lengths(format!(
+            "Code length {length} exceeds the maximum"
+        )));
+    }
+
+    let present: Vec<u8> = lengths.iter().copied().filter(|&l| l > 0).collect();
+    match present.as_slice() {
+        [] => Err(invalid_code_lengths(
+            "Code length table has no symbols".to_string(),
+        )),
+        [1] => Ok(()),
+        _ => {
+            let full = 1u128 << MAX_CANONICAL_CODE_LENGTH;
+            let kraft_sum: u128 = present
+                .iter()
+                .map(|&length| 1u128 << (MAX_CANONICAL_CODE_LENGTH - length))
+                .sum();
+            if kraft_sum == full {
+                Ok(())
+            } else if kraft_sum > full {
+                Err(invalid_code_lengths(
+                    "Code lengths are over-subscribed".to_string(),
+                ))
+            } else {
+                Err(invalid_code_lengths(
+                    "Code lengths leave the code incomplete".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+// Rebuilds a decoding tree from code lengths, following the canonical code assignment
+// A single present symbol becomes a lone leaf, like the tree the encoder started from
+// Rejects tables that do not describe a complete prefix code
+// Leaf frequencies are not stored in the stream, so every leaf gets a frequency of 1
+pub fn build_tree_from_code_lengths(lengths: &CodeLengths) -> HuffmanResult<HuffmanNode> {
+    build_canonical_tree(lengths, &|_| 1)
+}
+
+// Builds the canonical tree for code lengths computed by the encoder
+// Unlike a tree read back from a stream, the leaves keep their real frequencies
+pub(crate) fn build_canonical_tree_with_frequencies(
+    lengths: &CodeLengths,
+    frequency_map: &ByteFrequencyMap,
+) -> HuffmanResult<HuffmanNode> {
+    build_canonical_tree(lengths, &|symbol| {
+        frequency_map.get(&symbol).copied().unwrap_or_default()
+    })
+}
+
+// Validates the lengths, then lays the canonical codes out as a tree
+fn build_canonical_tree(
+    lengths: &CodeLengths,
+    frequency_of: &dyn Fn(u8) -> usize,
+) -> HuffmanResult<HuffmanNode> {
+    validate_code_lengths(lengths)?;
+
+    let codes = canonical_code_values(lengths);
+    match codes.as_slice() {
+        [(symbol, _, _)] => Ok(HuffmanNode::new_leaf(*symbol, frequency_of(*symbol))),
+        _ => Ok(build_subtree(&codes, 0, frequency_of)),
+    }
+}
+
+// Recursively splits codes sharing the same prefix by their next bit
+// Canonical codes in (length, symbol) order are also in lexicographic order,
+// so all codes continuing with '0' come before those continuing with '1'
+fn build_subtree(
+    codes: &[(u8, u64, u8)],
+    depth: u8,
+    frequency_of: &dyn Fn(u8) -> usize,
+) -> HuffmanNode {
+    match codes {
+        [(symbol, _, length)] if *length == depth => {
+            HuffmanNode::new_leaf(*symbol, frequency_of(*symbol))
+        }
+        _ => {
+            let bit_at_depth =
+                |&(_, code, length): &(u8, u64, u8)| (code >> (length - 1 - depth)) & 1;
+            let split = codes.partition_point(|entry| bit_at_depth(entry) == 0);
+            debug_assert!(
+                split > 0 && split < codes.len(),
+                "Complete code must branch both ways"
+            );
+            HuffmanNode::new_internal(
+                build_subtree(&codes[..split], depth + 1, frequency_of),
+                build_subtree(&codes[split..], depth + 1, frequency_of),
+            )
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::Write;
+
+const CRC32_POLYNOMIAL: u32 = 0xEDB8_8320;
+const CRC32_TABLE: [u32; 256] = build_crc32_table();
+
+// Precomputes the remainder of every possible byte for the reflected CRC-32 polynomial
+// Each entry is the result of running 8 shift-and-xor steps on the byte value
+// Evaluated at compile time so the table costs nothing at runtime
+const fn build_crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut remainder = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            remainder = if remainder & 1 != 0 {
+                (remainder >> 1) ^ CRC32_POLYNOMIAL
+            } else {
+                remainder >> 1
+            };
+            bit += 1;
+        }
+        table[index] = remainder;
+        index += 1;
+    }
+    table
+}
+
+// Incremental CRC-32 (IEEE 802.3, as used by zip and gzip) over a stream of bytes
+// Feed data with update() in as many pieces as needed, then read the result with value()
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    // Starts a new checksum with the standard all-ones initial state
+    pub fn new() -> Self {
+        Self { state: u32::MAX }
+    }
+
+    // Folds a slice of bytes into the running checksum using the lookup table
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.state = bytes.iter().fold(self.state, |state, &byte| {
+            CRC32_TABLE[((state ^ byte as u32) & 0xFF) as usize] ^ (state >> 8)
+        });
+    }
+
+    // Returns the checksum of all bytes seen so far (with the final inversion applied)
+    pub fn value(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Computes the CRC-32 of a complete byte slice in one call
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut checksum = Crc32::new();
+    checksum.update(bytes);
+    checksum.value()
+}
+
+// Writer adapter that checksums everything passing through it to the inner writer
+// Lets decoders verify their output without holding it in memory
+pub struct Crc32Writer<W> {
+    inner: W,
+    checksum: Crc32,
+}
+
+impl<W: Write> Crc32Writer<W> {
+    // Wraps a writer with a fresh checksum
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            checksum: Crc32::new(),
+        }
+    }
+
+    // Returns the checksum of all bytes accepted by the inner writer so far
+    pub fn checksum(&self) -> u32 {
+        self.checksum.value()
+    }
+}
+
+impl<W: Write> Write for Crc32Writer<W> {
+    // Forwards to the inner writer and checksums only the bytes it actually accepted
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let bytes_written = self.inner.write(buf)?;
+        self.checksum.update(&buf[..bytes_written]);
+        Ok(bytes_written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
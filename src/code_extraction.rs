@@ -1,42 +1,155 @@
+use crate::canonical_codes::{MAX_CANONICAL_CODE_LENGTH, SYMBOL_COUNT};
 use crate::tree_construction::HuffmanNode;
 use std::collections::HashMap;
+use std::fmt;
 
-pub type HuffmanCodeMap = HashMap<u8, String>;
+// A Huffman code packed into an integer: the lowest `len` bits of `bits`, most significant first
+// A length of zero means the symbol has no code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HuffmanCode {
+    pub bits: u64,
+    pub len: u8,
+}
+
+impl HuffmanCode {
+    // Creates a code from its value and length in bits
+    pub fn new(bits: u64, len: u8) -> Self {
+        debug_assert!(
+            len <= MAX_CANONICAL_CODE_LENGTH,
+            "Codes are at most {MAX_CANONICAL_CODE_LENGTH} bits, got {len}"
+        );
+        Self { bits, len }
+    }
+
+    // Returns the code extended by one more bit on the right
+    fn append_bit(self, bit: u64) -> Self {
+        Self::new((self.bits << 1) | bit, self.len + 1)
+    }
+}
+
+impl fmt::Display for HuffmanCode {
+    // Renders the code as a string of '0' and '1' characters, for debugging and tests
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.len as usize;
+        match self.len {
+            0 => Ok(()),
+            _ => write!(f, "{:0width$b}", self.bits),
+        }
+    }
+}
+
+// Lookup table from every byte value to its Huffman code
+// Backed by a 256-entry array, so the encoder finds a code with a single index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HuffmanCodeTable {
+    codes: [HuffmanCode; SYMBOL_COUNT],
+}
+
+impl Default for HuffmanCodeTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HuffmanCodeTable {
+    // Creates a table in which no symbol has a code
+    pub fn new() -> Self {
+        Self {
+            codes: [HuffmanCode::default(); SYMBOL_COUNT],
+        }
+    }
+
+    // Sets the code of a symbol, replacing any previous one
+    pub fn insert(&mut self, symbol: u8, code: HuffmanCode) {
+        self.codes[symbol as usize] = code;
+    }
+
+    // Returns the code of a symbol, or None if it has none
+    pub fn get(&self, symbol: u8) -> Option<HuffmanCode> {
+        Some(self.codes[symbol as usize]).filter(|code| code.len > 0)
+    }
+
+    // Returns the code of a symbol, with a zero length for symbols without one
+    // Writing a zero-length code emits nothing, so the encoder can use this unconditionally
+    pub fn code(&self, symbol: u8) -> HuffmanCode {
+        self.codes[symbol as usize]
+    }
+
+    // Returns true if the symbol has a code
+    pub fn contains(&self, symbol: u8) -> bool {
+        self.get(symbol).is_some()
+    }
+
+    // Returns the number of symbols that have a code
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    // Returns true if no symbol has a code
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Iterates over (symbol, code) pairs in symbol order, skipping symbols without a code
+    pub fn iter(&self) -> impl Iterator<Item = (u8, HuffmanCode)> + '_ {
+        self.codes
+            .iter()
+            .enumerate()
+            .filter(|(_, code)| code.len > 0)
+            .map(|(symbol, &code)| (symbol as u8, code))
+    }
+
+    // Renders every code as a '0'/'1' string keyed by symbol, for debugging and tests
+    pub fn to_string_map(&self) -> HashMap<u8, String> {
+        self.iter()
+            .map(|(symbol, code)| (symbol, code.to_string()))
+            .collect()
+    }
+}
+
+impl FromIterator<(u8, HuffmanCode)> for HuffmanCodeTable {
+    fn from_iter<I: IntoIterator<Item = (u8, HuffmanCode)>>(iter: I) -> Self {
+        let mut table = Self::new();
+        iter.into_iter()
+            .for_each(|(symbol, code)| table.insert(symbol, code));
+        table
+    }
+}
 
 // Recursively walks through the Huffman tree to extract binary codes
-// For leaf nodes: returns the symbol paired with its accumulated code
-// For internal nodes: explores both children, adding '0' for left, '1' for right
-// Combines all symbol-code pairs from the entire subtree into a single list
-fn extract_codes_recursive(node: &HuffmanNode, current_code: String) -> Vec<(u8, String)> {
+// For leaf nodes: records the symbol with its accumulated code
+// For internal nodes: explores both children, appending 0 for left, 1 for right
+// Stops with None below MAX_CANONICAL_CODE_LENGTH levels, where codes no longer fit a u64
+fn extract_codes_recursive(
+    node: &HuffmanNode,
+    current_code: HuffmanCode,
+    table: &mut HuffmanCodeTable,
+) -> Option<()> {
     match node.symbol() {
-        Some(symbol) => vec![(symbol, current_code)],
+        Some(symbol) => table.insert(symbol, current_code),
+        None if current_code.len >= MAX_CANONICAL_CODE_LENGTH => return None,
         None => {
-            let mut codes = Vec::new();
-
             if let Some(left) = node.left_child() {
-                codes.extend(extract_codes_recursive(left, format!("{current_code}0")));
+                extract_codes_recursive(left, current_code.append_bit(0), table)?;
             }
             if let Some(right) = node.right_child() {
-                codes.extend(extract_codes_recursive(right, format!("{current_code}1")));
+                extract_codes_recursive(right, current_code.append_bit(1), table)?;
             }
-
-            codes
         }
     }
+    Some(())
 }
 
 // Extracts all Huffman codes from a tree into a lookup table
-// Special case: if tree has only one symbol, assigns code "0"
-// General case: recursively walks tree to build codes, then converts to HashMap
-// Returns a map where each byte maps to its binary code string
-pub fn extract_huffman_codes(tree: &HuffmanNode) -> HuffmanCodeMap {
-    if tree.is_leaf() {
-        tree.symbol()
-            .map(|symbol| [(symbol, "0".to_string())].into_iter().collect())
-            .unwrap_or_default()
-    } else {
-        extract_codes_recursive(tree, String::new())
-            .into_iter()
-            .collect()
+// Special case: if tree has only one symbol, assigns the 1-bit code 0
+// General case: recursively walks tree to build codes
+// Returns None if the tree is deeper than MAX_CANONICAL_CODE_LENGTH, since codes are held
+// in a u64
+pub fn extract_huffman_codes(tree: &HuffmanNode) -> Option<HuffmanCodeTable> {
+    let mut table = HuffmanCodeTable::new();
+    match tree.symbol() {
+        Some(symbol) => table.insert(symbol, HuffmanCode::new(0, 1)),
+        None => extract_codes_recursive(tree, HuffmanCode::default(), &mut table)?,
     }
+    Some(table)
 }
@@ -0,0 +1,46 @@
+use crate::canonical_codes::CodeLengths;
+use crate::error::HuffmanResult;
+use crate::input_bit_stream::InputBitStream;
+use crate::output_bit_stream::OutputBitStream;
+use crate::run_length_table::{
+    least_run_length_table_bits, read_run_length_table, run_length_table_bits,
+    write_run_length_table,
+};
+use std::io::{Read, Write};
+
+// Code lengths are stored as they are; a length of 0 is an absent symbol
+const LENGTH_BIAS: u64 = 0;
+
+// Serializes the 256 code lengths of a canonical code in a compact run-length form
+// First 4 bits: the bit width used for each length (enough for the longest code)
+// Then, walking the symbols in order:
+// - '0' bit followed by 8 bits (run - 1) for a run of up to 256 absent symbols
+// - '1' bit followed by the length in `width` bits for a present symbol
+// Typical text blocks need a few hundred bits, versus 10 bits per symbol for a full tree shape
+pub fn serialize_code_lengths<W: Write>(
+    lengths: &CodeLengths,
+    bit_stream: &mut OutputBitStream<W>,
+) -> std::io::Result<()> {
+    write_run_length_table(&lengths.map(u64::from), LENGTH_BIAS, bit_stream)
+}
+
+// Number of bits serialize_code_lengths writes for the table, without writing it
+// Lets a compressor weigh the cost of storing a table before deciding to store it
+pub fn serialized_code_lengths_bits(lengths: &CodeLengths) -> usize {
+    run_length_table_bits(&lengths.map(u64::from), LENGTH_BIAS)
+}
+
+// Fewest bits any table with this many present symbols can take: lengths one bit wide and
+// all the absent symbols in a single run
+pub(crate) fn least_code_lengths_bits(present: usize) -> usize {
+    least_run_length_table_bits(present)
+}
+
+// Reads back a code length table written by serialize_code_lengths
+// Lengths wider than a byte and runs past the last symbol are rejected as a corrupt table
+pub fn deserialize_code_lengths<R: Read>(
+    bit_stream: &mut InputBitStream<R>,
+) -> HuffmanResult<CodeLengths> {
+    read_run_length_table(bit_stream, LENGTH_BIAS, u8::BITS)
+        .map(|lengths| lengths.map(|length| length as u8))
+}
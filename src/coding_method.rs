@@ -0,0 +1,54 @@
+use crate::adaptive_huffman::AdaptiveHuffmanModel;
+use crate::compression::CompressionOptions;
+use crate::context_huffman::ContextHuffmanCoder;
+use crate::decoder::{model_and_symbols, BlockSymbols};
+use crate::entropy_coder::{encode_block_with, EntropyCoder};
+use crate::error::HuffmanResult;
+use crate::huffman_coder::HuffmanCoder;
+use crate::range_coder::RangeCoder;
+use crate::tans_coder::TansCoder;
+
+// One coding method: the ids the container records for it and the coder behind them
+// Built from the coder itself, so the ids always agree with its METHOD and BLOCK_TYPE
+pub struct CodingMethod {
+    // Method id written to the file header
+    pub id: u8,
+    // Name of the method on the command line
+    pub name: &'static str,
+    // Block type of the blocks the coder writes
+    pub block_type: u8,
+    // Encodes a block with the coder, model first
+    pub(crate) encode_block: fn(&[u8], &CompressionOptions) -> std::io::Result<Vec<u8>>,
+    // Reads the model at the start of a payload, leaving the block's symbols to decode
+    pub(crate) start_block: fn(Vec<u8>, usize) -> HuffmanResult<BlockSymbols>,
+}
+
+const fn coded_with<C: EntropyCoder + 'static>(name: &'static str) -> CodingMethod {
+    CodingMethod {
+        id: C::METHOD,
+        name,
+        block_type: C::BLOCK_TYPE,
+        encode_block: encode_block_with::<C>,
+        start_block: model_and_symbols::<C>,
+    }
+}
+
+// Every coding method this version can write and read
+// Compression, decompression, the header checks and the command line all look methods up here
+pub static CODING_METHODS: [CodingMethod; 5] = [
+    coded_with::<HuffmanCoder>("huffman"),
+    coded_with::<AdaptiveHuffmanModel>("adaptive"),
+    coded_with::<RangeCoder>("range"),
+    coded_with::<TansCoder>("tans"),
+    coded_with::<ContextHuffmanCoder>("context"),
+];
+
+// Returns the coding method with the given id, or None if this version does not know it
+pub fn coding_method(id: u8) -> Option<&'static CodingMethod> {
+    CODING_METHODS.iter().find(|method| method.id == id)
+}
+
+// Returns the coding method with the given command line name
+pub fn coding_method_named(name: &str) -> Option<&'static CodingMethod> {
+    CODING_METHODS.iter().find(|method| method.name == name)
+}
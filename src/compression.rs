@@ -1,97 +1,191 @@
 use crate::{
-    code_extraction::extract_huffman_codes, frequency_map::count_frequencies,
-    output_bit_stream::OutputBitStream, tree_construction::build_huffman_tree,
-    tree_serialization::serialize_tree_to_bits,
+    block_format::{
+        write_block_header, BlockHeader, BLOCK_HEADER_SIZE, BLOCK_TYPE_STORED, DEFAULT_BLOCK_SIZE,
+    },
+    canonical_codes::MAX_CANONICAL_CODE_LENGTH,
+    coding_method::coding_method,
+    encoder::HuffmanEncoder,
+    error::{HuffmanError, HuffmanResult},
+    file_header::METHOD_HUFFMAN,
+    length_limited_codes::MIN_CODE_LENGTH_LIMIT,
 };
-use std::collections::HashMap;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Read, Write};
 
-// Converts a single byte to its Huffman code bits
-// Takes a byte value and looks it up in the codes table to get its binary representation
-// For example: if byte 'A' has code "101", this returns [1, 0, 1]
-// Returns None if the byte is not found in the codes table
-fn encode_byte(byte: u8, codes: &HashMap<u8, String>) -> Option<Vec<u8>> {
-    codes.get(&byte).map(|code| {
-        code.chars()
-            .map(|bit_char| if bit_char == '1' { 1 } else { 0 })
-            .collect()
-    })
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressionOptions {
+    pub block_size: usize,
+    // Longest code any block may use; None lets the Huffman tree grow as deep as it needs
+    // Only static Huffman blocks have a fixed code, so other methods ignore it
+    pub max_code_length: Option<u8>,
+    // Number of blocks encoded at the same time, each on its own thread
+    // The output is identical for any thread count; memory use grows to threads * block_size
+    pub threads: usize,
+    // Appends an index of block offsets after the trailer, so that decompress_parallel can
+    // hand blocks to several threads; costs 12 bytes per block
+    pub block_index: bool,
+    // Entropy coder for the blocks, named by its METHOD id; recorded in the file header
+    // METHOD_ADAPTIVE_HUFFMAN codes each byte as it arrives instead of counting the block first
+    pub method: u8,
 }
 
-// Encodes a stream of bytes into Huffman-coded bits
-// Takes an iterator of bytes (each wrapped in Result for error handling)
-// For each successful byte, looks up its Huffman code and converts to individual bits
-// Flattens all the bits into a single stream - so "AB" might become [1,0,1,1,1,0,0]
-// Preserves any IO errors that occurred while reading the input bytes
-fn encode_bytes<I: IntoIterator<Item = std::io::Result<u8>>>(
-    bytes: I,
-    codes: &HashMap<u8, String>,
-) -> impl Iterator<Item = std::io::Result<u8>> + use<'_, I> {
-    bytes
-        .into_iter()
-        .flat_map(move |byte_result| match byte_result {
-            Ok(byte) => encode_byte(byte, codes)
-                .map(|bits| bits.into_iter().map(Ok).collect::<Vec<_>>())
-                .unwrap_or_default(),
-            Err(e) => vec![Err(e)],
-        })
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        Self {
+            block_size: DEFAULT_BLOCK_SIZE,
+            max_code_length: None,
+            threads: 1,
+            block_index: false,
+            method: METHOD_HUFFMAN,
+        }
+    }
 }
 
-// Reads input in chunks and encodes each byte using Huffman codes
-// Uses a buffer to read 8KB chunks at a time for efficiency
-// For each chunk, converts bytes to Huffman bits and writes them to the bit stream
-// This avoids loading the entire file into memory while still using iterator patterns
-fn encode_input_stream<R: Read, W: std::io::Write>(
-    mut input_reader: R,
-    codes: &HashMap<u8, String>,
-    bit_stream: &mut OutputBitStream<W>,
+// Writes a block header followed by its payload
+fn write_block<W: Write>(
+    output_stream: &mut W,
+    block_type: u8,
+    original_length: usize,
+    payload: &[u8],
 ) -> std::io::Result<()> {
-    let mut buffer = [0u8; 8192];
+    let header = BlockHeader {
+        block_type,
+        original_length: original_length as u32,
+        payload_length: payload.len() as u32,
+    };
+    write_block_header(output_stream, &header).and_then(|_| output_stream.write_all(payload))
+}
 
-    loop {
-        let bytes_read = input_reader.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break;
-        }
+// Encodes one block and writes it with its header, returning the number of bytes written
+// The block is coded by the entropy coder of the method chosen in the options
+// Incompressible data (already compressed, random) would grow once the code lengths are added,
+// so such blocks are stored verbatim instead
+pub(crate) fn compress_block<W: Write>(
+    output_stream: &mut W,
+    block: &[u8],
+    options: &CompressionOptions,
+) -> std::io::Result<u64> {
+    let coding = coding_method(options.method).ok_or_else(|| unknown_method(options.method))?;
+    let payload = (coding.encode_block)(block, options)?;
+    let (block_type, stored) = if payload.len() < block.len() {
+        (coding.block_type, payload.as_slice())
+    } else {
+        (BLOCK_TYPE_STORED, block)
+    };
+    write_block(output_stream, block_type, block.len(), stored)
+        .map(|_| BLOCK_HEADER_SIZE + stored.len() as u64)
+}
 
-        let byte_iter = buffer[..bytes_read].iter().map(|&b| Ok(b));
-        for bit_result in encode_bytes(byte_iter, codes) {
-            let bit = bit_result?;
-            bit_stream.write_bit(bit)?;
-        }
+// Encodes a batch of consecutive blocks concurrently and writes them in input order
+// Step 1: Cut the batch into blocks of options.block_size bytes
+// Step 2: Encode each block, header included, into its own buffer on a scoped thread
+// Step 3: Write the buffers in order once every thread has finished
+// Blocks are self-contained, so the output is byte-for-byte what compress_block would
+// produce one block at a time; callers keep batches to at most options.threads blocks
+// Returns the number of bytes written for each block, in order
+pub(crate) fn compress_blocks<W: Write>(
+    output_stream: &mut W,
+    batch: &[u8],
+    options: &CompressionOptions,
+) -> std::io::Result<Vec<u64>> {
+    if options.threads == 1 || batch.len() <= options.block_size {
+        return batch
+            .chunks(options.block_size)
+            .map(|block| compress_block(output_stream, block, options))
+            .collect();
     }
 
+    let encoded_blocks = std::thread::scope(|scope| {
+        batch
+            .chunks(options.block_size)
+            .map(|block| {
+                scope.spawn(move || {
+                    let mut encoded = Vec::new();
+                    compress_block(&mut encoded, block, options).map(|_| encoded)
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(std::io::Error::other("Block encoder thread panicked")))
+            })
+            // Join every thread before looking at the results, so that a thread panicking
+            // after an earlier error cannot make the scope itself panic
+            .collect::<Vec<_>>()
+            .into_iter()
+            .collect::<std::io::Result<Vec<_>>>()
+    })?;
+
+    encoded_blocks
+        .iter()
+        .map(|encoded| {
+            output_stream
+                .write_all(encoded)
+                .map(|_| encoded.len() as u64)
+        })
+        .collect()
+}
+
+// Checks that the requested block size fits the block header's 32-bit length field,
+// that a code length limit leaves room for all 256 byte values, that at least one thread runs
+// and that the coding method is known
+pub(crate) fn validate_options(options: &CompressionOptions) -> HuffmanResult<()> {
+    if options.block_size == 0 || options.block_size > u32::MAX as usize {
+        return Err(HuffmanError::InvalidOptions(format!(
+            "Block size must be between 1 and {} bytes, got {}",
+            u32::MAX,
+            options.block_size
+        )));
+    }
+    if let Some(max_code_length) = options.max_code_length {
+        if !(MIN_CODE_LENGTH_LIMIT..=MAX_CANONICAL_CODE_LENGTH).contains(&max_code_length) {
+            return Err(HuffmanError::InvalidOptions(format!(
+                "Maximum code length must be between {MIN_CODE_LENGTH_LIMIT} and \
+                 {MAX_CANONICAL_CODE_LENGTH} bits, got {max_code_length}"
+            )));
+        }
+    }
+    if options.threads == 0 || options.block_size.checked_mul(options.threads).is_none() {
+        return Err(HuffmanError::InvalidOptions(format!(
+            "Thread count must be at least 1 and keep threads * block_size addressable, got {}",
+            options.threads
+        )));
+    }
+    if coding_method(options.method).is_none() {
+        return Err(unknown_method(options.method));
+    }
     Ok(())
 }
 
-// Compresses input data using Huffman coding algorithm
-// Step 1: Count how often each byte appears in the input
-// Step 2: Build a Huffman tree based on these frequencies
-// Step 3: Extract binary codes for each byte from the tree
-// Step 4: Write the original file size and tree structure to output
-// Step 5: Encode the actual data using the generated codes
-// Uses Result chaining (and_then) to handle errors gracefully at each step
-pub fn compress<R: Read + Seek, W: Write>(
+fn unknown_method(method: u8) -> HuffmanError {
+    HuffmanError::InvalidOptions(format!("Unknown coding method {method}"))
+}
+
+// Compresses any readable input in a single pass using bounded memory
+// Copies the input into a HuffmanEncoder, which cuts it into blocks of options.block_size,
+// encodes each block with its own tree and writes the header, end marker and trailer
+// Works with pipes, sockets and stdin since the input is never rewound
+pub fn compress_with_options<R: Read, W: Write>(
     mut input_reader: R,
     output_stream: &mut W,
-) -> std::io::Result<()> {
-    count_frequencies(&mut input_reader)
-        .and_then(|(frequency_map, total_bytes)| {
-            input_reader.seek(SeekFrom::Start(0))?;
-
-            let tree = build_huffman_tree(&frequency_map);
-            let codes = extract_huffman_codes(&tree);
-
-            Ok((tree, codes, total_bytes))
-        })
-        .and_then(|(tree, codes, total_bytes)| {
-            let original_length = total_bytes as u32;
-            output_stream.write_all(&original_length.to_le_bytes())?;
+    options: &CompressionOptions,
+) -> HuffmanResult<()> {
+    let mut encoder = HuffmanEncoder::with_options(output_stream, options.clone())?;
+    std::io::copy(&mut input_reader, &mut encoder)?;
+    encoder.finish().map(|_| ())
+}
 
-            let mut bit_stream = OutputBitStream::new(output_stream);
+// Compresses any readable input as a stream of blocks of the default size
+pub fn compress_stream<R: Read, W: Write>(
+    input_reader: R,
+    output_stream: &mut W,
+) -> HuffmanResult<()> {
+    compress_with_options(input_reader, output_stream, &CompressionOptions::default())
+}
 
-            serialize_tree_to_bits(&tree, &mut bit_stream)
-                .and_then(|_| encode_input_stream(&mut input_reader, &codes, &mut bit_stream))
-                .and_then(|_| bit_stream.flush())
-        })
+// Compresses input data using Huffman coding algorithm
+// Kept as the library's main entry point; every input is compressed as a stream of blocks
+pub fn compress<R: Read, W: Write>(input_reader: R, output_stream: &mut W) -> HuffmanResult<()> {
+    compress_stream(input_reader, output_stream)
 }
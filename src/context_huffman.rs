@@ -0,0 +1,301 @@
+use crate::block_format::BLOCK_TYPE_CONTEXT;
+use crate::canonical_codes::{CodeLengths, SYMBOL_COUNT};
+use crate::code_length_serialization::{least_code_lengths_bits, serialized_code_lengths_bits};
+use crate::compression::CompressionOptions;
+use crate::entropy_coder::EntropyCoder;
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::file_header::METHOD_CONTEXT_HUFFMAN;
+use crate::frequency_map::{count_context_frequencies, ByteFrequencyMap, INITIAL_CONTEXT};
+use crate::huffman_coder::HuffmanCoder;
+use crate::input_bit_stream::InputBitStream;
+use crate::output_bit_stream::OutputBitStream;
+use std::io::{Read, Write};
+
+const CONTEXT_COUNT_BITS: u32 = 9;
+const CONTEXT_BITS: u32 = 8;
+
+// Order-1 context modeling: each byte is coded with a Huffman table chosen by the byte
+// before it, so text and structured data get much shorter codes for their likely successors
+// Contexts worth a table of their own get one and the rest share one, all stored as code
+// lengths
+#[derive(Debug)]
+pub struct ContextHuffmanCoder {
+    // Index into tables for each previous byte; None if no table covers it
+    context_tables: [Option<usize>; SYMBOL_COUNT],
+    // Tables of single contexts in context order, then the shared table if there is one
+    tables: Vec<HuffmanCoder>,
+    has_shared: bool,
+    // The byte coded last, which picks the table for the next one
+    previous: u8,
+}
+
+impl ContextHuffmanCoder {
+    // Builds a coder from the frequency map of every context
+    // Step 1: Work out the code lengths plain Huffman coding would use for the whole block
+    // Step 2: Give a context its own table if its bytes take fewer bits with that table,
+    //         the table itself included, than with the code of the whole block
+    // Step 3: Merge the counts of all other contexts into one shared table
+    // Costs are worked out from code lengths alone, so only the tables kept are ever built,
+    // and contexts that could not win even with the cheapest possible table are not tried
+    // Returns None if no context has any bytes
+    pub fn from_context_frequencies(
+        context_frequencies: &[ByteFrequencyMap],
+        options: &CompressionOptions,
+    ) -> Option<Self> {
+        let block_lengths = HuffmanCoder::code_lengths_for(&merged(context_frequencies), options)?;
+
+        let mut context_tables = [None; SYMBOL_COUNT];
+        let mut tables = Vec::new();
+        let mut shared = Vec::new();
+        context_frequencies
+            .iter()
+            .enumerate()
+            .filter(|(_, frequency_map)| !frequency_map.is_empty())
+            .for_each(|(context, frequency_map)| {
+                let block_bits = coded_bits(&block_lengths, frequency_map);
+                let own_table = (least_own_table_bits(frequency_map) < block_bits)
+                    .then(|| HuffmanCoder::code_lengths_for(frequency_map, options))
+                    .flatten()
+                    .filter(|lengths| {
+                        serialized_code_lengths_bits(lengths) + coded_bits(lengths, frequency_map)
+                            < block_bits
+                    })
+                    .and_then(|lengths| HuffmanCoder::from_code_lengths(&lengths).ok());
+                match own_table {
+                    Some(table) => {
+                        context_tables[context] = Some(tables.len());
+                        tables.push(table);
+                    }
+                    None => shared.push(frequency_map),
+                }
+            });
+
+        let shared = HuffmanCoder::from_frequencies(&merged(shared), options);
+        Some(Self::with_shared(context_tables, tables, shared))
+    }
+
+    // Adds the shared table, if any, after the others and points every context without a
+    // table of its own at it
+    fn with_shared(
+        mut context_tables: [Option<usize>; SYMBOL_COUNT],
+        mut tables: Vec<HuffmanCoder>,
+        shared: Option<HuffmanCoder>,
+    ) -> Self {
+        let has_shared = shared.is_some();
+        if let Some(table) = shared {
+            context_tables
+                .iter_mut()
+                .filter(|context_table| context_table.is_none())
+                .for_each(|context_table| *context_table = Some(tables.len()));
+            tables.push(table);
+        }
+        Self {
+            context_tables,
+            tables,
+            has_shared,
+            previous: INITIAL_CONTEXT,
+        }
+    }
+
+    // Returns the table index of each context, None for contexts without a table
+    pub fn context_tables(&self) -> &[Option<usize>; SYMBOL_COUNT] {
+        &self.context_tables
+    }
+
+    // Returns the tables, those of single contexts first and the shared one (if any) last
+    pub fn tables(&self) -> &[HuffmanCoder] {
+        &self.tables
+    }
+
+    // Returns the table shared by the rarer contexts, if there is one
+    pub fn shared_table(&self) -> Option<&HuffmanCoder> {
+        self.has_shared.then(|| &self.tables[self.tables.len() - 1])
+    }
+
+    // Contexts that have a table of their own, in increasing order, with their tables
+    fn own_tables(&self) -> impl Iterator<Item = (u8, &HuffmanCoder)> {
+        let own_count = self.tables.len() - self.has_shared as usize;
+        (0..=255u8).filter_map(move |context| {
+            self.context_tables[context as usize]
+                .filter(|&table| table < own_count)
+                .map(|table| (context, &self.tables[table]))
+        })
+    }
+
+    // Returns the index of the table for the byte that follows `previous`
+    fn table_index(&self, previous: u8) -> HuffmanResult<usize> {
+        self.context_tables[previous as usize].ok_or_else(|| {
+            HuffmanError::CorruptBlock(format!("No code table for bytes following {previous}"))
+        })
+    }
+}
+
+// Adds up the counts of several frequency maps
+fn merged<'a>(frequency_maps: impl IntoIterator<Item = &'a ByteFrequencyMap>) -> ByteFrequencyMap {
+    frequency_maps.into_iter().flatten().fold(
+        ByteFrequencyMap::new(),
+        |mut merged, (&symbol, &count)| {
+            *merged.entry(symbol).or_insert(0) += count;
+            merged
+        },
+    )
+}
+
+// Bits taken by the codes of the counted bytes under the given code lengths
+// A code with a single symbol writes no bits at all
+fn coded_bits(lengths: &CodeLengths, frequency_map: &ByteFrequencyMap) -> usize {
+    if lengths.iter().filter(|&&length| length > 0).count() < 2 {
+        return 0;
+    }
+    frequency_map
+        .iter()
+        .map(|(&symbol, &count)| count * lengths[symbol as usize] as usize)
+        .sum()
+}
+
+// Fewest bits a table of the context's own could take for its bytes, the table included
+// No prefix code codes the bytes in fewer bits than the entropy of their counts
+fn least_own_table_bits(frequency_map: &ByteFrequencyMap) -> usize {
+    let total = frequency_map.values().sum::<usize>() as f64;
+    let entropy_bits: f64 = frequency_map
+        .values()
+        .map(|&count| count as f64 * (total / count as f64).log2())
+        .sum();
+    least_code_lengths_bits(frequency_map.len()) + entropy_bits.floor() as usize
+}
+
+impl EntropyCoder for ContextHuffmanCoder {
+    const METHOD: u8 = METHOD_CONTEXT_HUFFMAN;
+    const BLOCK_TYPE: u8 = BLOCK_TYPE_CONTEXT;
+
+    // Without per-context counts every context has to share a single table
+    fn from_frequencies(
+        frequency_map: &ByteFrequencyMap,
+        options: &CompressionOptions,
+    ) -> Option<Self> {
+        let shared = HuffmanCoder::from_frequencies(frequency_map, options)?;
+        Some(Self::with_shared(
+            [None; SYMBOL_COUNT],
+            Vec::new(),
+            Some(shared),
+        ))
+    }
+
+    // Counts every byte under the byte before it rather than on its own
+    fn for_block(block: &[u8], options: &CompressionOptions) -> std::io::Result<Option<Self>> {
+        Ok(Self::from_context_frequencies(
+            &count_context_frequencies(block),
+            options,
+        ))
+    }
+
+    // Serializes the context map followed by every table
+    // First 9 bits: how many contexts have a table of their own
+    // Then 8 bits for each of those contexts, in increasing order, and their code lengths
+    // Then a '1' bit followed by the code lengths of the shared table, or a '0' bit if none
+    // Every context that is not listed uses the shared table
+    fn serialize_model<W: Write>(
+        &self,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        let own_count = self.own_tables().count();
+        bit_stream.write_bits(own_count as u64, CONTEXT_COUNT_BITS)?;
+        self.own_tables()
+            .try_for_each(|(context, _)| bit_stream.write_bits(context as u64, CONTEXT_BITS))?;
+        self.own_tables()
+            .try_for_each(|(_, table)| table.serialize_model(bit_stream))?;
+
+        match self.shared_table() {
+            Some(table) => {
+                bit_stream.write_bit(1)?;
+                table.serialize_model(bit_stream)
+            }
+            None => bit_stream.write_bit(0),
+        }
+    }
+
+    // Reads a model written by serialize_model
+    // Rejects context lists that are too long or not in increasing order
+    fn deserialize_model<R: Read>(bit_stream: &mut InputBitStream<R>) -> HuffmanResult<Self> {
+        let count = bit_stream.read_bits(CONTEXT_COUNT_BITS)? as usize;
+        if count > SYMBOL_COUNT {
+            return Err(HuffmanError::CorruptTree(format!(
+                "{count} contexts listed, but there are only {SYMBOL_COUNT}"
+            )));
+        }
+        let own_contexts = (0..count)
+            .map(|_| {
+                bit_stream
+                    .read_bits(CONTEXT_BITS)
+                    .map(|context| context as usize)
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        if own_contexts.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(HuffmanError::CorruptTree(
+                "Contexts are not listed in increasing order".to_string(),
+            ));
+        }
+
+        let tables = (0..count)
+            .map(|_| HuffmanCoder::deserialize_model(bit_stream))
+            .collect::<HuffmanResult<Vec<_>>>()?;
+        let mut context_tables = [None; SYMBOL_COUNT];
+        own_contexts
+            .iter()
+            .enumerate()
+            .for_each(|(table, &context)| context_tables[context] = Some(table));
+
+        let shared = match bit_stream.read_bit()? {
+            1 => Some(HuffmanCoder::deserialize_model(bit_stream)?),
+            _ => None,
+        };
+        Ok(Self::with_shared(context_tables, tables, shared))
+    }
+
+    fn encode_symbol<W: Write>(
+        &mut self,
+        symbol: u8,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        let table = self.table_index(self.previous)?;
+        self.previous = symbol;
+        self.tables[table].encode_symbol(symbol, bit_stream)
+    }
+
+    fn decode_symbol<R: Read>(&mut self, bit_stream: &mut InputBitStream<R>) -> HuffmanResult<u8> {
+        let table = self.table_index(self.previous)?;
+        let symbol = self.tables[table].decode_symbol(bit_stream)?;
+        self.previous = symbol;
+        Ok(symbol)
+    }
+
+    // Decodes through the lookup table of every code table, built when the model was read,
+    // switching between them on each byte
+    fn decode_symbols<R: Read>(
+        &mut self,
+        bit_stream: &mut InputBitStream<R>,
+        count: usize,
+        buffer: &mut Vec<u8>,
+    ) -> HuffmanResult<()> {
+        let mut previous = self.previous;
+        let decoded = (0..count).try_for_each(|_| {
+            let table = self.table_index(previous)?;
+            previous = self.tables[table]
+                .decoding_table()
+                .decode_symbol(bit_stream)?;
+            buffer.push(previous);
+            Ok(())
+        });
+        self.previous = previous;
+        decoded
+    }
+
+    // A table holding a single symbol codes it without any bits; if every code takes at
+    // least one bit the default bound holds
+    fn max_symbols(&self, payload_length: usize) -> Option<u64> {
+        self.tables
+            .iter()
+            .all(|table| !table.tree().is_leaf())
+            .then_some(payload_length as u64 * 8)
+    }
+}
@@ -0,0 +1,377 @@
+use crate::{
+    block_format::{
+        block_type_fits_method, read_block_header, read_block_payload, read_stream_trailer,
+        BlockHeader, BLOCK_TYPE_HUFFMAN, BLOCK_TYPE_STORED,
+    },
+    block_index::{read_block_index_entries, BlockIndexEntry},
+    checksum::Crc32,
+    coding_method::coding_method,
+    decompression::{verify_trailer, DecompressionOptions},
+    entropy_coder::EntropyCoder,
+    error::{HuffmanError, HuffmanResult},
+    file_header::{read_file_header, FILE_HEADER_SIZE, METHOD_HUFFMAN},
+    huffman_coder::HuffmanCoder,
+    input_bit_stream::InputBitStream,
+    tree_deserialization::deserialize_tree,
+};
+use std::io::{BufRead, Cursor, Read};
+
+// Most bytes decoded ahead of the caller; together with one block's payload this bounds memory
+const DECODE_CHUNK_SIZE: usize = 1 << 16;
+
+// Where the symbols of the block being decoded come from
+pub(crate) enum BlockSymbols {
+    // Coded block: symbols are decoded from the payload by the block's entropy coder
+    Coded(Box<dyn SymbolSource>),
+    // Stored block: the payload is the original data
+    Stored(Cursor<Vec<u8>>),
+}
+
+// A coded block in the middle of being decoded, whatever its coder
+pub(crate) trait SymbolSource {
+    fn decode_symbols(&mut self, count: usize, buffer: &mut Vec<u8>) -> HuffmanResult<()>;
+}
+
+// An entropy coder together with the payload bits it decodes from
+struct CodedBlock<C> {
+    coder: C,
+    bit_stream: InputBitStream<Cursor<Vec<u8>>>,
+}
+
+impl<C: EntropyCoder> SymbolSource for CodedBlock<C> {
+    fn decode_symbols(&mut self, count: usize, buffer: &mut Vec<u8>) -> HuffmanResult<()> {
+        self.coder
+            .decode_symbols(&mut self.bit_stream, count, buffer)
+    }
+}
+
+// The block currently being decoded and how many of its bytes are still to come
+struct BlockState {
+    symbols: BlockSymbols,
+    remaining: usize,
+}
+
+// Decompressing reader: reading from it yields the original data of a compressed stream
+// Errors are returned as io::Error wrapping a HuffmanError; HuffmanError::from recovers it
+// Blocks are decoded lazily, a chunk at a time, as the caller reads; memory use is bounded
+// by one block's payload plus one chunk of output, whatever the size of the stream
+// The trailer is checked when the end marker is reached, so a length or checksum mismatch
+// is reported by the read that would otherwise return end of file
+// Block headers are untrusted: payloads are only allocated as far as the input really goes,
+// and blocks whose declared length would pass options.max_output_size are refused up front
+// A block index after the trailer is read too and must match the blocks that were decoded
+pub struct HuffmanDecoder<R: Read> {
+    reader: R,
+    options: DecompressionOptions,
+    header_read: bool,
+    method: u8,
+    checksum: Option<Crc32>,
+    block: Option<BlockState>,
+    buffer: Vec<u8>,
+    position: usize,
+    decoded_length: u64,
+    declared_length: u64,
+    // Position in the compressed stream and the blocks seen, kept only when there is an index
+    input_offset: u64,
+    seen_blocks: Option<Vec<BlockIndexEntry>>,
+    // Set once the end marker is read, and finished only once the trailer has been verified
+    end_reached: bool,
+    finished: bool,
+}
+
+impl<R: Read> HuffmanDecoder<R> {
+    // Creates a decoder reading a compressed stream from the given reader, without an output limit
+    // Nothing is read until the first call to read or fill_buf
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, DecompressionOptions::default())
+    }
+
+    // Creates a decoder that enforces the given limits
+    pub fn with_options(reader: R, options: DecompressionOptions) -> Self {
+        Self {
+            reader,
+            options,
+            header_read: false,
+            method: METHOD_HUFFMAN,
+            checksum: None,
+            block: None,
+            buffer: Vec::with_capacity(DECODE_CHUNK_SIZE),
+            position: 0,
+            decoded_length: 0,
+            declared_length: 0,
+            input_offset: FILE_HEADER_SIZE,
+            seen_blocks: None,
+            end_reached: false,
+            finished: false,
+        }
+    }
+
+    // Returns the inner reader, positioned wherever decoding stopped
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Reads the container header, noting the coding method and enabling checksum and block tracking if the stream has them
+    fn read_header(&mut self) -> HuffmanResult<()> {
+        let header = read_file_header(&mut self.reader)?;
+        self.method = header.method;
+        self.checksum = header.has_checksum().then(Crc32::new);
+        self.seen_blocks = header.has_block_index().then(Vec::new);
+        self.header_read = true;
+        Ok(())
+    }
+
+    // Checks the block's declared length against the output limit before anything is decoded
+    fn reserve_output(&mut self, block_header: &BlockHeader) -> HuffmanResult<()> {
+        self.declared_length += block_header.original_length as u64;
+        match self.options.max_output_size {
+            Some(limit) if self.declared_length > limit => Err(HuffmanError::LimitExceeded(
+                format!("Stream declares more than {limit} bytes of output"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Reads the next block's payload and prepares to decode it according to its type
+    fn start_block(&mut self, block_header: &BlockHeader) -> HuffmanResult<()> {
+        self.reserve_output(block_header)?;
+        let payload = read_block_payload(&mut self.reader, block_header)?;
+        self.block = Some(BlockState {
+            symbols: block_symbols(self.method, block_header, payload)?,
+            remaining: block_header.original_length as usize,
+        });
+        Ok(())
+    }
+
+    // Reads the trailer after the end marker and compares it with what was decoded
+    // When the stream has a block index, it must list exactly the blocks that were decoded
+    // The stream only counts as finished once every check has passed
+    fn finish_stream(&mut self) -> HuffmanResult<()> {
+        self.end_reached = true;
+        self.verify_end()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    fn verify_end(&mut self) -> HuffmanResult<()> {
+        let actual_checksum = self.checksum.as_ref().map(Crc32::value);
+        let trailer = read_stream_trailer(&mut self.reader, actual_checksum.is_some())?;
+        verify_trailer(&trailer, self.decoded_length, actual_checksum)?;
+
+        match self.seen_blocks.as_ref() {
+            Some(seen_blocks) => {
+                let entries = read_block_index_entries(&mut self.reader, seen_blocks.len())?;
+                match &entries == seen_blocks {
+                    true => Ok(()),
+                    false => Err(HuffmanError::CorruptIndex(
+                        "Index entries do not match the blocks in the stream".to_string(),
+                    )),
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Notes where a block starts and advances past it, when there is an index to check
+    fn track_block(&mut self, block_header: &BlockHeader) {
+        if let Some(seen_blocks) = self.seen_blocks.as_mut() {
+            if !block_header.is_end() {
+                seen_blocks.push(BlockIndexEntry {
+                    offset: self.input_offset,
+                    original_length: block_header.original_length,
+                });
+            }
+            self.input_offset += block_header.encoded_length();
+        }
+    }
+
+    // Decodes the next chunk of output into the internal buffer
+    // Step 1: Read the header on first use
+    // Step 2: Move on to the next block whenever the current one is exhausted
+    // Step 3: Decode up to DECODE_CHUNK_SIZE bytes of the current block
+    // Leaves the buffer empty only once the end of the stream has been reached and verified
+    fn refill(&mut self) -> HuffmanResult<()> {
+        self.buffer.clear();
+        self.position = 0;
+
+        while !self.finished {
+            // A stream whose trailer failed its checks keeps failing rather than ending
+            if self.end_reached {
+                return Err(HuffmanError::CorruptBlock(
+                    "The stream failed its final checks".to_string(),
+                ));
+            }
+            if !self.header_read {
+                self.read_header()?;
+            }
+
+            match self.block.as_mut() {
+                Some(block) if block.remaining > 0 => {
+                    let count = block.remaining.min(DECODE_CHUNK_SIZE);
+                    decode_chunk(&mut block.symbols, count, &mut self.buffer)?;
+                    block.remaining -= count;
+                    self.decoded_length += count as u64;
+                    if let Some(checksum) = self.checksum.as_mut() {
+                        checksum.update(&self.buffer);
+                    }
+                    return Ok(());
+                }
+                _ => {
+                    self.block = None;
+                    let block_header = read_block_header(&mut self.reader)?;
+                    self.track_block(&block_header);
+                    if block_header.is_end() {
+                        return self.finish_stream();
+                    }
+                    self.start_block(&block_header)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Prepares a block's payload for decoding according to the block type
+// The block type must be one the stream's coding method uses
+fn block_symbols(
+    method: u8,
+    block_header: &BlockHeader,
+    payload: Vec<u8>,
+) -> HuffmanResult<BlockSymbols> {
+    let payload_length = payload.len();
+    let original_length = block_header.original_length as usize;
+    if !block_type_fits_method(block_header.block_type, method) {
+        return Err(HuffmanError::CorruptBlock(format!(
+            "Block type {} does not belong to coding method {method}",
+            block_header.block_type
+        )));
+    }
+
+    match block_header.block_type {
+        BLOCK_TYPE_HUFFMAN => {
+            let mut bit_stream = InputBitStream::new(Cursor::new(payload));
+            let tree = deserialize_tree(&mut bit_stream)?;
+            coded_symbols(
+                HuffmanCoder::from_tree(tree)?,
+                bit_stream,
+                payload_length,
+                original_length,
+            )
+        }
+        BLOCK_TYPE_STORED => {
+            // The payload of a stored block is the original data, so both lengths must agree
+            if payload_length != original_length {
+                return Err(HuffmanError::CorruptBlock(format!(
+                    "Stored block holds {payload_length} bytes but declares {original_length}"
+                )));
+            }
+            Ok(BlockSymbols::Stored(Cursor::new(payload)))
+        }
+        // The block type fits the method, so it is the method's own
+        _ => match coding_method(method) {
+            Some(coding) => (coding.start_block)(payload, original_length),
+            None => Err(HuffmanError::UnsupportedMethod(method)),
+        },
+    }
+}
+
+// Decodes a whole block whose header and payload have already been read
+// Used to decode blocks independently of each other, on separate threads
+pub(crate) fn decode_block(
+    method: u8,
+    block_header: &BlockHeader,
+    payload: Vec<u8>,
+) -> HuffmanResult<Vec<u8>> {
+    decode_block_prefix(
+        method,
+        block_header,
+        payload,
+        block_header.original_length as usize,
+    )
+}
+
+// Decodes only the first `length` bytes of a block, for readers that need no more of it
+pub(crate) fn decode_block_prefix(
+    method: u8,
+    block_header: &BlockHeader,
+    payload: Vec<u8>,
+    length: usize,
+) -> HuffmanResult<Vec<u8>> {
+    let length = length.min(block_header.original_length as usize);
+    let mut symbols = block_symbols(method, block_header, payload)?;
+    // The declared length is untrusted, so the output grows as it is decoded
+    let mut decoded = Vec::new();
+    decode_chunk(&mut symbols, length, &mut decoded)?;
+    Ok(decoded)
+}
+
+// Reads the model at the start of a coded block's payload, leaving the symbols to decode
+pub(crate) fn model_and_symbols<C: EntropyCoder + 'static>(
+    payload: Vec<u8>,
+    original_length: usize,
+) -> HuffmanResult<BlockSymbols> {
+    let payload_length = payload.len();
+    let mut bit_stream = InputBitStream::new(Cursor::new(payload));
+    let coder = C::deserialize_model(&mut bit_stream)?;
+    coded_symbols(coder, bit_stream, payload_length, original_length)
+}
+
+// Prepares a coded block for decoding
+// A block declaring more symbols than its coder can fit in the payload is rejected here,
+// so it is not decoded at all
+fn coded_symbols<C: EntropyCoder + 'static>(
+    coder: C,
+    bit_stream: InputBitStream<Cursor<Vec<u8>>>,
+    payload_length: usize,
+    original_length: usize,
+) -> HuffmanResult<BlockSymbols> {
+    match coder.max_symbols(payload_length) {
+        Some(max_symbols) if original_length as u64 > max_symbols => {
+            Err(HuffmanError::CorruptBlock(format!(
+                "Block declares {original_length} symbols but its payload holds {payload_length} bytes"
+            )))
+        }
+        _ => Ok(BlockSymbols::Coded(Box::new(CodedBlock { coder, bit_stream }))),
+    }
+}
+
+// Appends the next `count` bytes of a block to the output buffer
+fn decode_chunk(
+    symbols: &mut BlockSymbols,
+    count: usize,
+    buffer: &mut Vec<u8>,
+) -> HuffmanResult<()> {
+    match symbols {
+        BlockSymbols::Coded(source) => source.decode_symbols(count, buffer),
+        BlockSymbols::Stored(payload) => {
+            payload.by_ref().take(count as u64).read_to_end(buffer)?;
+            Ok(())
+        }
+    }
+}
+
+impl<R: Read> BufRead for HuffmanDecoder<R> {
+    // Returns the decoded bytes not yet consumed, decoding another chunk when none are left
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.position == self.buffer.len() {
+            self.refill()?;
+        }
+        Ok(&self.buffer[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.buffer.len());
+    }
+}
+
+impl<R: Read> Read for HuffmanDecoder<R> {
+    // Copies as many decoded bytes as fit into buf, decoding more if none are buffered
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.consume(count);
+        Ok(count)
+    }
+}
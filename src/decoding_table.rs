@@ -0,0 +1,72 @@
+use crate::decompression::decode_next_symbol;
+use crate::error::HuffmanResult;
+use crate::input_bit_stream::InputBitStream;
+use crate::tree_construction::HuffmanNode;
+use std::io::Read;
+
+// Number of bits resolved by a single table lookup
+// Codes up to this length decode in one step; longer (rare) codes fall back to the tree
+pub const TABLE_BITS: u32 = 10;
+
+// A table slot: the symbol and code length for every code up to TABLE_BITS long,
+// or None when the slot's bits are only the prefix of a longer code
+type TableEntry = Option<(u8, u8)>;
+
+// Lookup table for decoding symbols from a Huffman tree several bits at a time
+// Every TABLE_BITS-bit pattern indexes the entry of the code it starts with,
+// so a code of length L fills 2^(TABLE_BITS - L) consecutive slots
+// The table keeps its own copy of the tree, so a coder can build it once and hold on to it
+#[derive(Debug)]
+pub struct DecodingTable {
+    tree: HuffmanNode,
+    entries: Vec<TableEntry>,
+}
+
+impl DecodingTable {
+    // Builds the table by walking the tree down to TABLE_BITS levels
+    // Subtrees that continue below that depth are left as None and decoded by walking the tree
+    pub fn new(tree: &HuffmanNode) -> Self {
+        let mut entries = vec![None; 1 << TABLE_BITS];
+        fill_entries(tree, 0, 0, &mut entries);
+        Self {
+            tree: tree.clone(),
+            entries,
+        }
+    }
+
+    // Decodes the next symbol from the bit stream
+    // Step 1: Peek TABLE_BITS bits (zero-padded at the end of the input)
+    // Step 2: If the slot holds a code, consume only that code's length and return its symbol
+    // Step 3: Otherwise the code is longer than the table, so walk the tree from the root
+    pub fn decode_symbol<R: Read>(&self, bit_stream: &mut InputBitStream<R>) -> HuffmanResult<u8> {
+        let index = bit_stream.peek_bits(TABLE_BITS)? as usize;
+        match self.entries[index] {
+            Some((symbol, length)) => {
+                bit_stream.consume(length as u32)?;
+                Ok(symbol)
+            }
+            None => decode_next_symbol(&self.tree, bit_stream),
+        }
+    }
+}
+
+// Recursively fills the slots covered by each leaf within the first TABLE_BITS levels
+// `code` holds the `depth` bits of the path from the root to `node`
+fn fill_entries(node: &HuffmanNode, code: usize, depth: u32, entries: &mut [TableEntry]) {
+    match node.symbol() {
+        Some(symbol) => {
+            let shift = TABLE_BITS - depth;
+            let first = code << shift;
+            entries[first..first + (1 << shift)].fill(Some((symbol, depth as u8)));
+        }
+        None if depth < TABLE_BITS => {
+            if let Some(left) = node.left_child() {
+                fill_entries(left, code << 1, depth + 1, entries);
+            }
+            if let Some(right) = node.right_child() {
+                fill_entries(right, (code << 1) | 1, depth + 1, entries);
+            }
+        }
+        None => {}
+    }
+}
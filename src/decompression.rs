@@ -1,87 +1,290 @@
+use crate::block_format::{
+    read_block_header, read_block_payload, read_stream_trailer, BlockHeader, StreamTrailer,
+};
+use crate::block_index::{read_block_index, BlockIndexEntry};
+use crate::checksum::Crc32;
+use crate::decoder::{decode_block, HuffmanDecoder};
+use crate::decoding_table::DecodingTable;
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::file_header::{read_file_header, FILE_HEADER_SIZE};
 use crate::input_bit_stream::InputBitStream;
 use crate::tree_construction::HuffmanNode;
-use crate::tree_deserialization::deserialize_tree;
-use std::io::{Read, Write};
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
 
 const LEFT_BIT: u8 = 0;
 const RIGHT_BIT: u8 = 1;
 
+// Decoded output is written in chunks of this many bytes
+const OUTPUT_CHUNK_SIZE: usize = 8192;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecompressionOptions {
+    // Most bytes a stream may decompress to; None accepts whatever the block headers declare
+    // Set this when decoding untrusted input, since a few bytes can declare gigabytes of output
+    pub max_output_size: Option<u64>,
+    // Number of blocks decompress_parallel decodes at the same time; the sequential
+    // decoder ignores it
+    pub threads: usize,
+}
+
+impl Default for DecompressionOptions {
+    fn default() -> Self {
+        Self {
+            max_output_size: None,
+            threads: 1,
+        }
+    }
+}
+
 // Decompresses Huffman-encoded data back to original form
-// Step 1: Read the 4-byte header containing original file size
-// Step 2: Deserialize the Huffman tree from the bit stream
-// Step 3: Use the tree to decode the compressed data back to original bytes
-// Uses Result chaining (and_then) to handle errors at each step
-pub fn decompress<R: Read, W: Write>(
+// Reads the stream through a HuffmanDecoder and writes each decoded chunk straight from
+// the decoder's buffer, so only one block's payload and one chunk of output are in memory
+// The decoder checks the header, every block, and the trailer's total length and CRC-32
+// The coding method is read from the header, so streams of every method decode the same way
+pub fn decompress<R: Read, W: Write>(input_reader: R, output_stream: &mut W) -> HuffmanResult<()> {
+    decompress_with_options(
+        input_reader,
+        output_stream,
+        &DecompressionOptions::default(),
+    )
+}
+
+// Decompresses like decompress, failing with LimitExceeded before writing any block that
+// would take the output past options.max_output_size
+pub fn decompress_with_options<R: Read, W: Write>(
     input_reader: R,
     output_stream: &mut W,
-) -> std::io::Result<()> {
-    let mut reader = input_reader;
-    let mut header_bytes = [0u8; 4];
-    reader
-        .read_exact(&mut header_bytes)
-        .map(|_| u32::from_le_bytes(header_bytes) as usize)
-        .and_then(|original_length| {
-            let mut bit_stream = InputBitStream::new(reader);
-            deserialize_tree(&mut bit_stream).and_then(|tree| {
-                decode_compressed_data(&tree, &mut bit_stream, output_stream, original_length)
+    options: &DecompressionOptions,
+) -> HuffmanResult<()> {
+    let mut decoder = HuffmanDecoder::with_options(input_reader, options.clone());
+
+    loop {
+        let decoded = decoder.fill_buf()?;
+        if decoded.is_empty() {
+            return Ok(());
+        }
+        output_stream.write_all(decoded)?;
+        let count = decoded.len();
+        decoder.consume(count);
+    }
+}
+
+// Decompresses a seekable stream, decoding several blocks at once when it has a block index
+// Step 1: Read the file header; without an index (or with one thread) decode sequentially
+// Step 2: Read the index from the end of the stream and check the declared output size
+// Step 3: Read batches of options.threads blocks, checking each against its index entry
+// Step 4: Decode every block of a batch on its own scoped thread, then write them in order
+// Step 5: Check the end marker and the trailer, as the sequential decoder does
+// Produces exactly the bytes decompress does; each block is decoded whole, so memory grows
+// to threads * block size, which options.max_output_size bounds for untrusted input
+pub fn decompress_parallel<R: Read + Seek, W: Write>(
+    mut input_reader: R,
+    output_stream: &mut W,
+    options: &DecompressionOptions,
+) -> HuffmanResult<()> {
+    if options.threads == 0 {
+        return Err(HuffmanError::InvalidOptions(
+            "Thread count must be at least 1".to_string(),
+        ));
+    }
+
+    let start = input_reader.stream_position()?;
+    let header = read_file_header(&mut input_reader)?;
+    if !header.has_block_index() || options.threads == 1 {
+        input_reader.seek(SeekFrom::Start(start))?;
+        return decompress_with_options(input_reader, output_stream, options);
+    }
+
+    let index = read_block_index(&mut input_reader, start)?;
+    let declared_length: u64 = index.iter().map(|entry| entry.original_length as u64).sum();
+    if let Some(limit) = options
+        .max_output_size
+        .filter(|&limit| declared_length > limit)
+    {
+        return Err(HuffmanError::LimitExceeded(format!(
+            "Stream declares more than {limit} bytes of output"
+        )));
+    }
+
+    let mut checksum = header.has_checksum().then(Crc32::new);
+    let mut next_offset = FILE_HEADER_SIZE;
+    input_reader.seek(SeekFrom::Start(start + next_offset))?;
+
+    for batch in index.chunks(options.threads) {
+        let blocks = batch
+            .iter()
+            .map(|entry| {
+                let block = read_indexed_block(&mut input_reader, entry, next_offset)?;
+                next_offset += block.0.encoded_length();
+                Ok(block)
             })
-        })
+            .collect::<HuffmanResult<Vec<_>>>()?;
+
+        let decoded_blocks = std::thread::scope(|scope| {
+            blocks
+                .into_iter()
+                .map(|(block_header, payload)| {
+                    scope.spawn(move || decode_block(header.method, &block_header, payload))
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        Err(HuffmanError::Io(std::io::Error::other(
+                            "Block decoder thread panicked",
+                        )))
+                    })
+                })
+                // Join every thread before looking at the results, so that a thread panicking
+                // after an earlier error cannot make the scope itself panic
+                .collect::<Vec<_>>()
+                .into_iter()
+                .collect::<HuffmanResult<Vec<_>>>()
+        })?;
+
+        decoded_blocks.iter().try_for_each(|decoded| {
+            if let Some(checksum) = checksum.as_mut() {
+                checksum.update(decoded);
+            }
+            output_stream.write_all(decoded)
+        })?;
+    }
+
+    if !read_block_header(&mut input_reader)?.is_end() {
+        return Err(HuffmanError::CorruptIndex(
+            "Index ends before the last block".to_string(),
+        ));
+    }
+    let actual_checksum = checksum.as_ref().map(Crc32::value);
+    let trailer = read_stream_trailer(&mut input_reader, actual_checksum.is_some())?;
+    verify_trailer(&trailer, declared_length, actual_checksum)
+}
+
+// Reads the block an index entry points to, which must start where the previous block ended
+// and declare the length the index records
+fn read_indexed_block<R: Read>(
+    input_reader: &mut R,
+    entry: &BlockIndexEntry,
+    expected_offset: u64,
+) -> HuffmanResult<(BlockHeader, Vec<u8>)> {
+    let block_header = read_block_header(input_reader)?;
+    if entry.offset != expected_offset
+        || block_header.is_end()
+        || block_header.original_length != entry.original_length
+    {
+        return Err(HuffmanError::CorruptIndex(format!(
+            "Entry for offset {} does not match the block at offset {expected_offset}",
+            entry.offset
+        )));
+    }
+    let payload = read_block_payload(input_reader, &block_header)?;
+    Ok((block_header, payload))
+}
+
+// Compares the trailer with what was actually decoded
+// A length or CRC-32 mismatch means blocks were lost or corrupted
+pub(crate) fn verify_trailer(
+    trailer: &StreamTrailer,
+    decoded_length: u64,
+    actual_checksum: Option<u32>,
+) -> HuffmanResult<()> {
+    if trailer.original_length != decoded_length {
+        return Err(HuffmanError::LengthMismatch {
+            expected: trailer.original_length,
+            actual: decoded_length,
+        });
+    }
+
+    match (trailer.checksum, actual_checksum) {
+        (Some(expected), Some(actual)) if expected != actual => {
+            Err(HuffmanError::ChecksumMismatch { expected, actual })
+        }
+        _ => Ok(()),
+    }
 }
 
 // Decodes compressed data using the Huffman tree
 // Handles two cases: single symbol trees (where all data is the same character)
-// and multi-symbol trees (where we traverse the tree using bits to find symbols)
+// and multi-symbol trees, decoded through a lookup table built from the tree
 pub fn decode_compressed_data<R: Read, W: Write>(
     tree: &HuffmanNode,
     bit_stream: &mut InputBitStream<R>,
     output_stream: &mut W,
     original_length: usize,
-) -> std::io::Result<()> {
+) -> HuffmanResult<()> {
     if tree.is_leaf() {
         decode_single_symbol_tree(tree, output_stream, original_length)
     } else {
-        decode_multi_symbol_tree(tree, bit_stream, output_stream, original_length)
+        let table = DecodingTable::new(tree);
+        decode_multi_symbol_data(
+            || table.decode_symbol(bit_stream),
+            output_stream,
+            original_length,
+        )
+    }
+}
+
+// Decodes compressed data by walking the Huffman tree one bit per step
+// Much slower than decode_compressed_data, but simple enough to check the table decoder against
+pub fn decode_compressed_data_by_tree_walk<R: Read, W: Write>(
+    tree: &HuffmanNode,
+    bit_stream: &mut InputBitStream<R>,
+    output_stream: &mut W,
+    original_length: usize,
+) -> HuffmanResult<()> {
+    if tree.is_leaf() {
+        decode_single_symbol_tree(tree, output_stream, original_length)
+    } else {
+        decode_multi_symbol_data(
+            || decode_next_symbol(tree, bit_stream),
+            output_stream,
+            original_length,
+        )
     }
 }
 
 // Handles the special case where all input was the same character
-// Simply repeats the single symbol for the required number of times
-// Uses repeat_n to generate the required number of copies efficiently
+// Repeats the single symbol for the required number of times
+// Writes a fixed-size chunk at a time so huge outputs never sit in memory
 fn decode_single_symbol_tree<W: Write>(
     tree: &HuffmanNode,
     output_stream: &mut W,
     output_length: usize,
-) -> std::io::Result<()> {
+) -> HuffmanResult<()> {
     let symbol = tree.symbol().expect("Leaf node must have a symbol");
-    let symbols = std::iter::repeat_n(symbol, output_length).collect::<Vec<_>>();
-    output_stream.write_all(&symbols)
-}
+    let chunk = [symbol; OUTPUT_CHUNK_SIZE];
 
-// Creates an iterator that decodes the specified number of symbols
-// Each iteration reads bits from the stream and traverses the Huffman tree
-// until it reaches a leaf node, then returns the symbol at that leaf
-// Returns an iterator of Results to handle any IO errors during decoding
-fn decode_symbols<'a, R: Read>(
-    tree: &'a HuffmanNode,
-    bit_stream: &'a mut InputBitStream<R>,
-    count: usize,
-) -> impl Iterator<Item = std::io::Result<u8>> + 'a {
-    (0..count).map(move |_| decode_next_symbol(tree, bit_stream))
+    (0..output_length)
+        .step_by(OUTPUT_CHUNK_SIZE)
+        .try_for_each(|written| {
+            output_stream.write_all(&chunk[..OUTPUT_CHUNK_SIZE.min(output_length - written)])
+        })
+        .map_err(HuffmanError::from)
 }
 
-// Decodes data from a tree with multiple different symbols
-// Uses the decode_symbols iterator to get all the decoded bytes
-// Collects them into a vector and writes the entire result at once
-// This is more efficient than writing each symbol individually
-fn decode_multi_symbol_tree<R: Read, W: Write>(
-    tree: &HuffmanNode,
-    bit_stream: &mut InputBitStream<R>,
+// Decodes the specified number of symbols with the given symbol decoder
+// Collects decoded bytes into a fixed-size chunk and writes each chunk at once,
+// which is more efficient than writing each symbol individually
+fn decode_multi_symbol_data<F, W>(
+    mut decode_symbol: F,
     output_stream: &mut W,
     output_length: usize,
-) -> std::io::Result<()> {
-    let symbols: Result<Vec<_>, _> = decode_symbols(tree, bit_stream, output_length).collect();
-    let symbols = symbols?;
-    output_stream.write_all(&symbols)
+) -> HuffmanResult<()>
+where
+    F: FnMut() -> HuffmanResult<u8>,
+    W: Write,
+{
+    let mut chunk = Vec::with_capacity(OUTPUT_CHUNK_SIZE.min(output_length));
+
+    (0..output_length)
+        .step_by(OUTPUT_CHUNK_SIZE)
+        .try_for_each(|written| {
+            chunk.clear();
+            (0..OUTPUT_CHUNK_SIZE.min(output_length - written))
+                .try_for_each(|_| decode_symbol().map(|symbol| chunk.push(symbol)))?;
+            output_stream.write_all(&chunk)?;
+            Ok(())
+        })
 }
 
 // Decodes a single symbol by traversing the Huffman tree
@@ -90,29 +293,31 @@ fn decode_multi_symbol_tree<R: Read, W: Write>(
 // - 1 bit = go to right child
 // Continues until reaching a leaf node, then returns the symbol at that leaf
 // Uses successors to generate a sequence of tree nodes based on input bits
-fn decode_next_symbol<R: Read>(
+pub(crate) fn decode_next_symbol<R: Read>(
     tree: &HuffmanNode,
     bit_stream: &mut InputBitStream<R>,
-) -> std::io::Result<u8> {
+) -> HuffmanResult<u8> {
     std::iter::successors(Some(Ok(tree)), |node_result| {
         node_result.as_ref().ok().and_then(|node| {
             if node.is_leaf() {
                 None
             } else {
-                Some(bit_stream.read_bit().and_then(|bit| {
-                    match bit {
-                        LEFT_BIT => Ok(node
-                            .left_child()
-                            .expect("Internal node must have left child")),
-                        RIGHT_BIT => Ok(node
-                            .right_child()
-                            .expect("Internal node must have right child")),
-                        _ => Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Invalid bit value: {bit}"),
-                        )),
-                    }
-                }))
+                Some(
+                    bit_stream
+                        .read_bit()
+                        .map_err(HuffmanError::from)
+                        .and_then(|bit| match bit {
+                            LEFT_BIT => Ok(node
+                                .left_child()
+                                .expect("Internal node must have left child")),
+                            RIGHT_BIT => Ok(node
+                                .right_child()
+                                .expect("Internal node must have right child")),
+                            _ => Err(HuffmanError::CorruptTree(format!(
+                                "Invalid bit value: {bit}"
+                            ))),
+                        }),
+                )
             }
         })
     })
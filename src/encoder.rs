@@ -0,0 +1,153 @@
+use crate::{
+    block_format::{write_block_header, write_stream_trailer, BlockHeader, StreamTrailer},
+    block_index::{write_block_index, BlockIndexEntry},
+    checksum::Crc32,
+    compression::{compress_blocks, validate_options, CompressionOptions},
+    error::HuffmanResult,
+    file_header::{write_file_header, FileHeader, FILE_HEADER_SIZE, FLAG_BLOCK_INDEX, FLAG_CRC32},
+};
+use std::io::Write;
+
+// Compressing writer: everything written to it comes out of the inner writer compressed
+// Data is collected into a batch of options.threads blocks of options.block_size bytes each;
+// a full batch is encoded with options.method, its blocks side by side, and written as soon
+// as more data arrives; the compressed bytes are the same whatever the thread count
+// Up to block_size * threads bytes of input are buffered, 64 MiB with 1 MiB blocks on 64
+// threads; the buffer grows with the data written rather than being reserved up front
+// With options.block_index, the offset of every block is recorded and written after the trailer
+// finish() must be called to write the last block, the end marker and the trailer;
+// dropping the encoder without it leaves a truncated stream
+pub struct HuffmanEncoder<W: Write> {
+    writer: W,
+    options: CompressionOptions,
+    batch: Vec<u8>,
+    batch_size: usize,
+    checksum: Crc32,
+    total_bytes: u64,
+    header_written: bool,
+    bytes_written: u64,
+    index: Vec<BlockIndexEntry>,
+}
+
+impl<W: Write> HuffmanEncoder<W> {
+    // Creates an encoder with the default options
+    pub fn new(writer: W) -> Self {
+        Self::with_options(writer, CompressionOptions::default())
+            .expect("Default options are valid")
+    }
+
+    // Creates an encoder with the given options
+    // Fails with InvalidOptions if the options are out of range
+    pub fn with_options(writer: W, options: CompressionOptions) -> HuffmanResult<Self> {
+        validate_options(&options)?;
+        let batch_size = options.block_size * options.threads;
+        Ok(Self {
+            writer,
+            batch: Vec::new(),
+            batch_size,
+            options,
+            checksum: Crc32::new(),
+            total_bytes: 0,
+            header_written: false,
+            bytes_written: 0,
+            index: Vec::new(),
+        })
+    }
+
+    // Writes the container header before the first block
+    fn write_header_once(&mut self) -> std::io::Result<()> {
+        if !self.header_written {
+            let flags = match self.options.block_index {
+                true => FLAG_CRC32 | FLAG_BLOCK_INDEX,
+                false => FLAG_CRC32,
+            };
+            let header = FileHeader::new(flags).with_method(self.options.method);
+            write_file_header(&mut self.writer, &header)?;
+            self.header_written = true;
+            self.bytes_written = FILE_HEADER_SIZE;
+        }
+        Ok(())
+    }
+
+    // Encodes the buffered data as self-contained blocks and empties the buffer
+    // Only the last block may be shorter than options.block_size
+    // Does nothing when no data is buffered, so flushing twice never emits empty blocks
+    fn emit_blocks(&mut self) -> std::io::Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        // The batch only counts towards the trailer once it has been written, so that a
+        // failed emit can be retried without counting it twice
+        self.write_header_once()?;
+        let encoded_lengths = compress_blocks(&mut self.writer, &self.batch, &self.options)?;
+        self.checksum.update(&self.batch);
+        self.total_bytes += self.batch.len() as u64;
+        self.batch
+            .chunks(self.options.block_size)
+            .zip(encoded_lengths)
+            .for_each(|(block, encoded_length)| {
+                if self.options.block_index {
+                    self.index.push(BlockIndexEntry {
+                        offset: self.bytes_written,
+                        original_length: block.len() as u32,
+                    });
+                }
+                self.bytes_written += encoded_length;
+            });
+        self.batch.clear();
+        Ok(())
+    }
+
+    // Completes the stream and returns the inner writer
+    // Step 1: Encode whatever is still buffered as the last block
+    // Step 2: Terminate the blocks with an end marker
+    // Step 3: Write the trailer with the total original length and the CRC-32 of all input
+    // Step 4: Write the block index if one was requested
+    pub fn finish(mut self) -> HuffmanResult<W> {
+        self.write_header_once()?;
+        self.emit_blocks()?;
+        write_block_header(&mut self.writer, &BlockHeader::end())?;
+        write_stream_trailer(
+            &mut self.writer,
+            &StreamTrailer {
+                original_length: self.total_bytes,
+                checksum: Some(self.checksum.value()),
+            },
+        )?;
+        if self.options.block_index {
+            write_block_index(&mut self.writer, &self.index)?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    // Returns a reference to the inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl<W: Write> Write for HuffmanEncoder<W> {
+    // Buffers as much of buf as fits in the current batch of blocks
+    // A full batch is encoded and written before any more bytes are taken, so an error
+    // always means that none of buf was accepted and the same bytes can be written again
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.batch.len() == self.batch_size {
+            self.emit_blocks()?;
+        }
+
+        let room = self.batch_size - self.batch.len();
+        let accepted = buf.len().min(room);
+        self.batch.extend_from_slice(&buf[..accepted]);
+        Ok(accepted)
+    }
+
+    // Encodes the buffered data, ending with a (possibly short) block, and flushes the inner writer
+    // Everything written so far can then be decoded, at the cost of an extra block header
+    // and code length table per flush
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.emit_blocks()?;
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,99 @@
+use crate::compression::CompressionOptions;
+use crate::error::HuffmanResult;
+use crate::frequency_map::{count_frequencies, ByteFrequencyMap};
+use crate::input_bit_stream::InputBitStream;
+use crate::output_bit_stream::OutputBitStream;
+use std::io::{Read, Write};
+
+// A way of turning the bytes of a block into bits and back
+// Every coder shares the container, the bit streams and the CLI; the file header records which
+// coder wrote a stream (METHOD) and each block header names the coder's block type
+// A coded block is the serialized model followed by the coded symbols, so blocks stay
+// independent of each other and can be decoded in any order
+// A coder becomes a coding method by being listed in CODING_METHODS
+pub trait EntropyCoder: Sized {
+    // Method id written to the file header of streams coded with this coder
+    const METHOD: u8;
+    // Block type of the blocks this coder writes
+    const BLOCK_TYPE: u8;
+
+    // Builds the model for a block from its byte counts; None if the block is empty
+    fn from_frequencies(
+        frequency_map: &ByteFrequencyMap,
+        options: &CompressionOptions,
+    ) -> Option<Self>;
+
+    // Writes what the decoder needs to rebuild the model before any symbol is decoded
+    fn serialize_model<W: Write>(&self, bit_stream: &mut OutputBitStream<W>)
+        -> std::io::Result<()>;
+
+    // Reads a model written by serialize_model
+    fn deserialize_model<R: Read>(bit_stream: &mut InputBitStream<R>) -> HuffmanResult<Self>;
+
+    // Writes the code of one symbol
+    // Coders that can only code a block as a whole may hold it back until encode_symbols
+    fn encode_symbol<W: Write>(
+        &mut self,
+        symbol: u8,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()>;
+
+    // Reads the code of one symbol
+    fn decode_symbol<R: Read>(&mut self, bit_stream: &mut InputBitStream<R>) -> HuffmanResult<u8>;
+
+    // Builds the model for a block by counting its bytes
+    // Coders that need no counts override this to skip the counting pass
+    fn for_block(block: &[u8], options: &CompressionOptions) -> std::io::Result<Option<Self>> {
+        let (frequency_map, _) = count_frequencies(block)?;
+        Ok(Self::from_frequencies(&frequency_map, options))
+    }
+
+    // Writes the codes of a whole block, called once per block after the model
+    // Coders that keep state between symbols override this to flush it at the end
+    fn encode_symbols<W: Write>(
+        &mut self,
+        symbols: &[u8],
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        symbols
+            .iter()
+            .try_for_each(|&symbol| self.encode_symbol(symbol, bit_stream))
+    }
+
+    // Appends the next `count` symbols to the buffer; a block may be decoded in several calls
+    fn decode_symbols<R: Read>(
+        &mut self,
+        bit_stream: &mut InputBitStream<R>,
+        count: usize,
+        buffer: &mut Vec<u8>,
+    ) -> HuffmanResult<()> {
+        (0..count).try_for_each(|_| {
+            self.decode_symbol(bit_stream)
+                .map(|symbol| buffer.push(symbol))
+        })
+    }
+
+    // Most symbols a payload of this many bytes can decode to, or None if there is no bound
+    // The decoder refuses blocks that declare more before decoding any of them
+    // By default every symbol is assumed to take at least one bit
+    fn max_symbols(&self, payload_length: usize) -> Option<u64> {
+        Some(payload_length as u64 * 8)
+    }
+}
+
+// Encodes a block with the given coder: its model, then its symbols, padded to a whole byte
+// An empty block has an empty payload
+pub fn encode_block_with<C: EntropyCoder>(
+    block: &[u8],
+    options: &CompressionOptions,
+) -> std::io::Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    if let Some(mut coder) = C::for_block(block, options)? {
+        let mut bit_stream = OutputBitStream::new(&mut payload);
+        coder
+            .serialize_model(&mut bit_stream)
+            .and_then(|_| coder.encode_symbols(block, &mut bit_stream))
+            .and_then(|_| bit_stream.flush())?;
+    }
+    Ok(payload)
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+use std::io;
+
+// Every way compressing or decompressing can fail, so callers can match on the cause
+// Library functions return HuffmanResult; the Read and Write adapters, which must return
+// io::Error, wrap a HuffmanError inside it and converting back recovers the original variant
+#[derive(Debug)]
+pub enum HuffmanError {
+    // Reading the input or writing the output failed
+    Io(io::Error),
+    // The data does not start with the container's magic number
+    BadMagic([u8; 4]),
+    // The container was written by a format version this library cannot read
+    UnsupportedVersion(u8),
+    // The container header sets flags this library does not know
+    UnsupportedFlags(u8),
+    // The container header names a coding method this library does not know
+    UnsupportedMethod(u8),
+    // A block header names a block type this library cannot decode
+    UnknownBlockType(u8),
+    // A serialized tree, code length table or frequency table does not describe a usable model
+    CorruptTree(String),
+    // A block's payload disagrees with its header
+    CorruptBlock(String),
+    // The block index disagrees with the blocks it describes
+    CorruptIndex(String),
+    // The decoded data does not have the CRC-32 recorded in the trailer
+    ChecksumMismatch { expected: u32, actual: u32 },
+    // The decoded data does not have the length recorded in the trailer
+    LengthMismatch { expected: u64, actual: u64 },
+    // The compressed data ended before the stream was complete
+    TruncatedStream,
+    // The stream asks for more than the configured limits allow
+    LimitExceeded(String),
+    // The compression options are out of range
+    InvalidOptions(String),
+}
+
+pub type HuffmanResult<T> = Result<T, HuffmanError>;
+
+impl HuffmanError {
+    // Returns the io::ErrorKind this error maps to when converted into an io::Error
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            HuffmanError::Io(error) => error.kind(),
+            HuffmanError::UnsupportedVersion(_)
+            | HuffmanError::UnsupportedFlags(_)
+            | HuffmanError::UnsupportedMethod(_) => io::ErrorKind::Unsupported,
+            HuffmanError::TruncatedStream => io::ErrorKind::UnexpectedEof,
+            HuffmanError::InvalidOptions(_) => io::ErrorKind::InvalidInput,
+            HuffmanError::BadMagic(_)
+            | HuffmanError::UnknownBlockType(_)
+            | HuffmanError::CorruptTree(_)
+            | HuffmanError::CorruptBlock(_)
+            | HuffmanError::CorruptIndex(_)
+            | HuffmanError::ChecksumMismatch { .. }
+            | HuffmanError::LengthMismatch { .. }
+            | HuffmanError::LimitExceeded(_) => io::ErrorKind::InvalidData,
+        }
+    }
+}
+
+impl fmt::Display for HuffmanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HuffmanError::Io(error) => write!(f, "I/O error: {error}"),
+            HuffmanError::BadMagic(magic) => {
+                write!(f, "Not a compressed stream: bad magic number {magic:02X?}")
+            }
+            HuffmanError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported format version: {version}")
+            }
+            HuffmanError::UnsupportedFlags(flags) => {
+                write!(f, "Unsupported header flags: {flags:#04X}")
+            }
+            HuffmanError::UnsupportedMethod(method) => {
+                write!(f, "Unsupported coding method: {method}")
+            }
+            HuffmanError::UnknownBlockType(block_type) => {
+                write!(f, "Unknown block type: {block_type}")
+            }
+            HuffmanError::CorruptTree(message) => write!(f, "Corrupt code table: {message}"),
+            HuffmanError::CorruptBlock(message) => write!(f, "Corrupt block: {message}"),
+            HuffmanError::CorruptIndex(message) => write!(f, "Corrupt block index: {message}"),
+            HuffmanError::ChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Checksum mismatch: expected {expected:08X}, got {actual:08X}"
+                )
+            }
+            HuffmanError::LengthMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Length mismatch: expected {expected} bytes, decoded {actual}"
+                )
+            }
+            HuffmanError::TruncatedStream => write!(f, "Compressed stream is truncated"),
+            HuffmanError::LimitExceeded(message) => write!(f, "Limit exceeded: {message}"),
+            HuffmanError::InvalidOptions(message) => write!(f, "Invalid options: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for HuffmanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HuffmanError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HuffmanError {
+    // Recovers a HuffmanError that was wrapped into an io::Error by a Read or Write adapter
+    // Running out of input is reported as TruncatedStream; anything else stays an Io error
+    fn from(error: io::Error) -> Self {
+        if error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<HuffmanError>())
+        {
+            let inner = error
+                .into_inner()
+                .expect("Checked that an inner error exists");
+            return *inner
+                .downcast::<HuffmanError>()
+                .expect("Checked that the inner error is a HuffmanError");
+        }
+
+        match error.kind() {
+            io::ErrorKind::UnexpectedEof => HuffmanError::TruncatedStream,
+            _ => HuffmanError::Io(error),
+        }
+    }
+}
+
+impl From<HuffmanError> for io::Error {
+    // Io errors are unwrapped; every other cause is kept inside an io::Error of matching kind
+    fn from(error: HuffmanError) -> Self {
+        match error {
+            HuffmanError::Io(error) => error,
+            error => io::Error::new(error.kind(), error),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::coding_method::coding_method;
+use crate::error::{HuffmanError, HuffmanResult};
+use std::io::{Read, Write};
+
+pub const MAGIC: [u8; 4] = *b"HUFF";
+pub const FORMAT_VERSION: u8 = 4;
+pub const FLAG_CRC32: u8 = 0x01;
+pub const FLAG_BLOCK_INDEX: u8 = 0x02;
+pub const SUPPORTED_FLAGS: u8 = FLAG_CRC32 | FLAG_BLOCK_INDEX;
+
+// Coding methods: which EntropyCoder coded the blocks of a stream
+// Static Huffman codes chosen per block from its byte counts, stored as code lengths
+pub const METHOD_HUFFMAN: u8 = 0;
+// Adaptive Huffman codes updated after every byte, with nothing stored but the coded data
+pub const METHOD_ADAPTIVE_HUFFMAN: u8 = 1;
+// Range coding over scaled byte counts, spending fractions of a bit on very common bytes
+pub const METHOD_RANGE: u8 = 2;
+// Table-based asymmetric numeral systems (tANS, as in zstd's FSE) over normalized byte counts
+pub const METHOD_TANS: u8 = 3;
+// Static Huffman codes with a separate table for the bytes following each byte value
+pub const METHOD_CONTEXT_HUFFMAN: u8 = 4;
+
+// Bytes taken by the header: magic number, version, flags and method
+pub const FILE_HEADER_SIZE: u64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    pub version: u8,
+    pub flags: u8,
+    pub method: u8,
+}
+
+impl FileHeader {
+    // Creates a header for the current format version with the given flags
+    // The stream is coded with static Huffman codes; see with_method for the alternatives
+    pub fn new(flags: u8) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            flags,
+            method: METHOD_HUFFMAN,
+        }
+    }
+
+    // Returns the same header with another coding method
+    pub fn with_method(self, method: u8) -> Self {
+        Self { method, ..self }
+    }
+
+    // Returns true if the stream trailer carries a CRC-32 of the original data
+    pub fn has_checksum(&self) -> bool {
+        self.flags & FLAG_CRC32 != 0
+    }
+
+    // Returns true if a block index follows the stream trailer
+    pub fn has_block_index(&self) -> bool {
+        self.flags & FLAG_BLOCK_INDEX != 0
+    }
+}
+
+// Writes the container header that precedes every compressed stream
+// Layout: 4 magic bytes, 1 version byte, 1 flags byte, 1 method byte
+// The original length is not known up front when streaming, so it lives in the trailer
+pub fn write_file_header<W: Write>(writer: &mut W, header: &FileHeader) -> std::io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[header.version, header.flags, header.method])
+}
+
+// Reads and validates the container header at the start of a compressed stream
+// Unknown magic bytes are reported as BadMagic (the input is not one of our files)
+// Unknown versions, flags or methods are reported as unsupported (a newer writer produced it)
+pub fn read_file_header<R: Read>(reader: &mut R) -> HuffmanResult<FileHeader> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(HuffmanError::BadMagic(magic));
+    }
+
+    let mut fields = [0u8; 3];
+    reader.read_exact(&mut fields)?;
+    let [version, flags, method] = fields;
+    if version != FORMAT_VERSION {
+        return Err(HuffmanError::UnsupportedVersion(version));
+    }
+    if flags & !SUPPORTED_FLAGS != 0 {
+        return Err(HuffmanError::UnsupportedFlags(flags));
+    }
+    if coding_method(method).is_none() {
+        return Err(HuffmanError::UnsupportedMethod(method));
+    }
+
+    Ok(FileHeader {
+        version,
+        flags,
+        method,
+    })
+}
@@ -36,3 +36,36 @@ pub fn count_frequencies<R: Read>(mut reader: R) -> std::io::Result<(ByteFrequen
     })
     .map(|frequency_map| (frequency_map, total_bytes))
 }
+
+// Context of the first byte of a block, which has no previous byte
+pub const INITIAL_CONTEXT: u8 = 0;
+
+// Counts how often each byte value follows each byte value in a block
+// Entry c of the result is the frequency map of the bytes whose previous byte is c;
+// the first byte is counted under INITIAL_CONTEXT, and contexts that never occur stay empty
+// Counts go into one flat array first, which is much cheaper than a map update per byte,
+// and only the rows of contexts that occur are turned into maps
+pub fn count_context_frequencies(block: &[u8]) -> Vec<ByteFrequencyMap> {
+    let mut counts = vec![0u32; 256 * 256];
+    let mut context_seen = [false; 256];
+    std::iter::once(INITIAL_CONTEXT)
+        .chain(block.iter().copied())
+        .zip(block)
+        .for_each(|(context, &byte)| {
+            context_seen[context as usize] = true;
+            counts[(context as usize) << 8 | byte as usize] += 1;
+        });
+
+    counts
+        .chunks(256)
+        .zip(context_seen)
+        .map(|(row, seen)| match seen {
+            true => (0..=255u8)
+                .zip(row)
+                .filter(|&(_, &count)| count > 0)
+                .map(|(byte, &count)| (byte, count as usize))
+                .collect(),
+            false => ByteFrequencyMap::new(),
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+use crate::canonical_codes::SYMBOL_COUNT;
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::frequency_map::ByteFrequencyMap;
+use crate::input_bit_stream::InputBitStream;
+use crate::output_bit_stream::OutputBitStream;
+use crate::run_length_table::{read_run_length_table, write_run_length_table};
+use std::io::{Read, Write};
+
+// Every present symbol has a frequency of at least 1, so frequencies are stored minus 1
+const FREQUENCY_BIAS: u64 = 1;
+
+// Frequency of every byte value scaled to a power-of-two total; zero means absent
+pub type ScaledFrequencies = [u32; SYMBOL_COUNT];
+
+// Scales the counts of a block so that they add up to 2^scale_bits
+// Every present symbol keeps a frequency of at least 1; a shortfall from rounding down goes
+// to the largest frequency, and an excess from raising rare symbols to 1 is taken back one at
+// a time from whichever frequency is largest, so no symbol ever drops to 0
+// scale_bits must leave room for every symbol (at least 8) and fit the table (at most 16)
+// Returns None for an empty map
+pub fn normalize_frequencies(
+    frequency_map: &ByteFrequencyMap,
+    scale_bits: u32,
+) -> Option<ScaledFrequencies> {
+    let target = 1u32 << scale_bits;
+    let total: u64 = frequency_map.values().map(|&count| count as u64).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut scaled = [0u32; SYMBOL_COUNT];
+    frequency_map.iter().for_each(|(&symbol, &count)| {
+        scaled[symbol as usize] = ((count as u64 * target as u64 / total) as u32).max(1);
+    });
+
+    let mut sum: u32 = scaled.iter().sum();
+    while sum != target {
+        let largest = (0..SYMBOL_COUNT)
+            .max_by_key(|&symbol| (scaled[symbol], std::cmp::Reverse(symbol)))
+            .unwrap_or(0);
+        if sum < target {
+            scaled[largest] += target - sum;
+            sum = target;
+        } else {
+            scaled[largest] -= 1;
+            sum -= 1;
+        }
+    }
+    Some(scaled)
+}
+
+// Serializes scaled frequencies in the run-length form shared with serialize_code_lengths,
+// each present frequency stored as (frequency - 1) so that a 16-bit table fits 16-bit values
+pub fn serialize_frequencies<W: Write>(
+    frequencies: &ScaledFrequencies,
+    bit_stream: &mut OutputBitStream<W>,
+) -> std::io::Result<()> {
+    write_run_length_table(&frequencies.map(u64::from), FREQUENCY_BIAS, bit_stream)
+}
+
+// Reads back a frequency table written by serialize_frequencies
+// Rejects runs past the last symbol and tables that do not add up to 2^scale_bits
+pub fn deserialize_frequencies<R: Read>(
+    bit_stream: &mut InputBitStream<R>,
+    scale_bits: u32,
+) -> HuffmanResult<ScaledFrequencies> {
+    let frequencies = read_run_length_table(bit_stream, FREQUENCY_BIAS, u32::BITS)?
+        .map(|frequency| frequency as u32);
+
+    let sum: u32 = frequencies.iter().sum();
+    let target = 1u32 << scale_bits;
+    if sum != target {
+        return Err(HuffmanError::CorruptTree(format!(
+            "Frequencies add up to {sum} instead of {target}"
+        )));
+    }
+    Ok(frequencies)
+}
@@ -0,0 +1,182 @@
+use crate::block_format::BLOCK_TYPE_CANONICAL;
+use crate::canonical_codes::{
+    assign_canonical_codes, build_tree_from_code_lengths, code_lengths_from_tree,
+    huffman_code_lengths, CodeLengths, MAX_CANONICAL_CODE_LENGTH,
+};
+use crate::code_extraction::{extract_huffman_codes, HuffmanCodeTable};
+use crate::code_length_serialization::{deserialize_code_lengths, serialize_code_lengths};
+use crate::compression::CompressionOptions;
+use crate::decoding_table::DecodingTable;
+use crate::decompression::decode_next_symbol;
+use crate::entropy_coder::EntropyCoder;
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::file_header::METHOD_HUFFMAN;
+use crate::frequency_map::ByteFrequencyMap;
+use crate::input_bit_stream::InputBitStream;
+use crate::length_limited_codes::length_limited_code_lengths;
+use crate::output_bit_stream::OutputBitStream;
+use crate::tree_construction::HuffmanNode;
+use std::cell::OnceCell;
+use std::io::{Read, Write};
+
+// Static Huffman coding: one code per block, chosen from the block's byte counts
+// The model is stored as code lengths and both sides derive the same canonical codes from them
+#[derive(Debug)]
+pub struct HuffmanCoder {
+    tree: HuffmanNode,
+    codes: HuffmanCodeTable,
+    // Lookup table for decoding, built the first time it is needed and kept for the block
+    decoding_table: OnceCell<DecodingTable>,
+}
+
+impl HuffmanCoder {
+    // Builds the coder for a table of code lengths: the canonical codes to encode with
+    // and the matching tree to decode with
+    // A single-symbol table needs no data bits at all, so it gets no codes
+    pub fn from_code_lengths(lengths: &CodeLengths) -> HuffmanResult<Self> {
+        let tree = build_tree_from_code_lengths(lengths)?;
+        let codes = match tree.is_leaf() {
+            true => HuffmanCodeTable::new(),
+            false => assign_canonical_codes(lengths),
+        };
+        Ok(Self {
+            tree,
+            codes,
+            decoding_table: OnceCell::new(),
+        })
+    }
+
+    // Wraps a tree whose codes follow its own shape rather than the canonical order,
+    // as in the legacy blocks that store the whole tree; only used to decode such blocks
+    // Rejects trees too deep for their codes to fit in MAX_CANONICAL_CODE_LENGTH bits
+    pub(crate) fn from_tree(tree: HuffmanNode) -> HuffmanResult<Self> {
+        let codes = match tree.is_leaf() {
+            true => HuffmanCodeTable::new(),
+            false => extract_huffman_codes(&tree).ok_or_else(|| {
+                HuffmanError::CorruptTree(format!(
+                    "Tree is deeper than {MAX_CANONICAL_CODE_LENGTH} levels"
+                ))
+            })?,
+        };
+        Ok(Self {
+            tree,
+            codes,
+            decoding_table: OnceCell::new(),
+        })
+    }
+
+    // Code lengths of the Huffman code for the counts, limited in depth if requested
+    // Counts skewed enough to need codes over MAX_CANONICAL_CODE_LENGTH bits get the best
+    // code within that length instead
+    // Returns None if there is nothing to code
+    pub(crate) fn code_lengths_for(
+        frequency_map: &ByteFrequencyMap,
+        options: &CompressionOptions,
+    ) -> Option<CodeLengths> {
+        match options.max_code_length {
+            Some(max_code_length) => length_limited_code_lengths(frequency_map, max_code_length),
+            None => huffman_code_lengths(frequency_map)
+                .filter(|lengths| {
+                    lengths
+                        .iter()
+                        .all(|&length| length <= MAX_CANONICAL_CODE_LENGTH)
+                })
+                .or_else(|| length_limited_code_lengths(frequency_map, MAX_CANONICAL_CODE_LENGTH)),
+        }
+    }
+
+    // Returns the decoding tree
+    pub fn tree(&self) -> &HuffmanNode {
+        &self.tree
+    }
+
+    // Returns the lookup table for decoding, building it on first use
+    pub fn decoding_table(&self) -> &DecodingTable {
+        self.decoding_table
+            .get_or_init(|| DecodingTable::new(&self.tree))
+    }
+}
+
+impl EntropyCoder for HuffmanCoder {
+    const METHOD: u8 = METHOD_HUFFMAN;
+    const BLOCK_TYPE: u8 = BLOCK_TYPE_CANONICAL;
+
+    // Builds a Huffman tree from the counts and keeps only each symbol's code length so that
+    // the codes are canonical
+    fn from_frequencies(
+        frequency_map: &ByteFrequencyMap,
+        options: &CompressionOptions,
+    ) -> Option<Self> {
+        Self::code_lengths_for(frequency_map, options)
+            .and_then(|lengths| Self::from_code_lengths(&lengths).ok())
+    }
+
+    fn serialize_model<W: Write>(
+        &self,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        serialize_code_lengths(&code_lengths_from_tree(&self.tree), bit_stream)
+    }
+
+    // A coder read from a stream is there to decode, so its lookup table is built right away
+    // and reused by every later call to decode_symbols
+    fn deserialize_model<R: Read>(bit_stream: &mut InputBitStream<R>) -> HuffmanResult<Self> {
+        let coder = Self::from_code_lengths(&deserialize_code_lengths(bit_stream)?)?;
+        coder.decoding_table();
+        Ok(coder)
+    }
+
+    // Bytes without a code have a zero-length entry and produce no bits
+    fn encode_symbol<W: Write>(
+        &mut self,
+        symbol: u8,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        let code = self.codes.code(symbol);
+        bit_stream.write_bits(code.bits, code.len as u32)
+    }
+
+    // Walks the tree one bit per level; decode_symbols is much faster for whole runs
+    fn decode_symbol<R: Read>(&mut self, bit_stream: &mut InputBitStream<R>) -> HuffmanResult<u8> {
+        match self.tree.symbol() {
+            Some(symbol) => Ok(symbol),
+            None => decode_next_symbol(&self.tree, bit_stream),
+        }
+    }
+
+    // A single-symbol tree repeats its symbol without reading any bits, copying whole runs
+    // so that huge single-symbol blocks stay cheap to expand
+    // Other trees decode through the lookup table
+    fn decode_symbols<R: Read>(
+        &mut self,
+        bit_stream: &mut InputBitStream<R>,
+        count: usize,
+        buffer: &mut Vec<u8>,
+    ) -> HuffmanResult<()> {
+        match self.tree.symbol() {
+            Some(symbol) => {
+                let run = [symbol; 4096];
+                (0..count).step_by(run.len()).for_each(|start| {
+                    buffer.extend_from_slice(&run[..run.len().min(count - start)])
+                });
+                Ok(())
+            }
+            None => {
+                let table = self.decoding_table();
+                (0..count).try_for_each(|_| {
+                    table
+                        .decode_symbol(bit_stream)
+                        .map(|symbol| buffer.push(symbol))
+                })
+            }
+        }
+    }
+
+    // Every code of a multi-symbol tree takes at least one bit; a single-symbol tree takes none
+    fn max_symbols(&self, payload_length: usize) -> Option<u64> {
+        match self.tree.is_leaf() {
+            true => None,
+            false => Some(payload_length as u64 * 8),
+        }
+    }
+}
@@ -1,59 +1,132 @@
-use crate::constants::{BITS_PER_BYTE, MSB_MASK};
+use crate::constants::BITS_PER_BYTE;
 use std::io::Read;
 
+// Most bits peek_bits can look ahead; refilling a byte at a time must never overflow the u64
+pub const MAX_PEEK_BITS: u32 = u64::BITS - BITS_PER_BYTE as u32;
+
+// Number of bytes requested from the underlying reader at a time
+const READ_BUFFER_SIZE: usize = 8192;
+
 pub struct InputBitStream<R> {
     reader: R,
-    current_byte: u8,
-    bits_in_current_byte: usize,
+    // Bytes read from the reader in bulk but not yet moved into the bit buffer
+    read_buffer: Vec<u8>,
+    read_position: usize,
+    // Bits loaded from the reader but not consumed yet, aligned to the most significant end
+    // Everything below the loaded bits is kept zero, so peeking past the end reads zeros
+    bit_buffer: u64,
+    bits_in_buffer: u32,
 }
 
 impl<R: Read> InputBitStream<R> {
     // Creates a new bit stream reader from any byte-oriented reader
-    // Initializes with no current byte loaded and no bits available
+    // Initializes with empty byte and bit buffers
+    // The reader is read ahead in chunks, so it should not be shared with other consumers
     pub fn new(reader: R) -> Self {
         Self {
             reader,
-            current_byte: 0,
-            bits_in_current_byte: 0,
+            read_buffer: Vec::new(),
+            read_position: 0,
+            bit_buffer: 0,
+            bits_in_buffer: 0,
+        }
+    }
+
+    // Returns the next byte of input, reading a new chunk from the reader when needed
+    // Returns None once the reader is exhausted
+    fn next_byte(&mut self) -> std::io::Result<Option<u8>> {
+        if self.read_position == self.read_buffer.len() {
+            self.read_buffer.resize(READ_BUFFER_SIZE, 0);
+            let bytes_read = loop {
+                match self.reader.read(&mut self.read_buffer) {
+                    Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                    result => break result,
+                }
+            };
+            self.read_position = 0;
+            match bytes_read {
+                Ok(bytes_read) => self.read_buffer.truncate(bytes_read),
+                Err(error) => {
+                    self.read_buffer.clear();
+                    return Err(error);
+                }
+            }
         }
+
+        let byte = self.read_buffer.get(self.read_position).copied();
+        self.read_position += byte.is_some() as usize;
+        Ok(byte)
     }
 
-    // Loads the next byte from the underlying reader
-    // Reads exactly one byte and sets up bit tracking for that byte
-    // Resets the bit counter to 8, indicating 8 bits are now available
-    fn load_next_byte(&mut self) -> std::io::Result<()> {
-        let mut buffer = [0u8; 1];
-        self.reader.read_exact(&mut buffer)?;
-        self.current_byte = buffer[0];
-        self.bits_in_current_byte = BITS_PER_BYTE;
+    // Tops up the bit buffer with whole bytes until it holds at least `count` bits
+    // Stops early at the end of the input, leaving fewer bits buffered
+    fn refill(&mut self, count: u32) -> std::io::Result<()> {
+        while self.bits_in_buffer < count {
+            match self.next_byte()? {
+                Some(byte) => {
+                    let shift = u64::BITS - BITS_PER_BYTE as u32 - self.bits_in_buffer;
+                    self.bit_buffer |= (byte as u64) << shift;
+                    self.bits_in_buffer += BITS_PER_BYTE as u32;
+                }
+                None => break,
+            }
+        }
         Ok(())
     }
 
     // Reads a single bit from the stream
-    // If no bits are available in current byte, loads the next byte first
-    // Extracts the most significant bit from current_byte using bitwise AND with 128
-    // Shifts the byte left to position the next bit as the most significant
     // Returns the extracted bit as 0 or 1
     pub fn read_bit(&mut self) -> std::io::Result<u8> {
-        debug_assert!(
-            self.bits_in_current_byte <= BITS_PER_BYTE,
-            "Invalid bit count state: {} bits remaining in current byte",
-            self.bits_in_current_byte
-        );
+        self.read_bits(1).map(|bit| bit as u8)
+    }
 
-        if self.bits_in_current_byte == 0 {
-            self.load_next_byte()?;
+    // Reads `count` bits (at most 64), most significant first, and consumes them
+    // Fails with UnexpectedEof if the input ends before that many bits are available
+    pub fn read_bits(&mut self, count: u32) -> std::io::Result<u64> {
+        debug_assert!(count <= u64::BITS, "Can read at most 64 bits, got {count}");
+
+        if count > MAX_PEEK_BITS {
+            let high = self.read_bits(count - u32::BITS)?;
+            return self
+                .read_bits(u32::BITS)
+                .map(|low| (high << u32::BITS) | low);
+        }
+        if count == 0 {
+            return Ok(0);
         }
 
-        let bit = if self.current_byte & MSB_MASK != 0 {
-            1
-        } else {
-            0
-        };
+        let value = self.peek_bits(count)?;
+        self.consume(count).map(|_| value)
+    }
+
+    // Returns the next `count` bits, most significant first, without consuming them
+    // Loads bytes until enough bits are buffered or the reader runs out;
+    // past the end of the input the missing bits read as zeros
+    pub fn peek_bits(&mut self, count: u32) -> std::io::Result<u64> {
+        debug_assert!(
+            (1..=MAX_PEEK_BITS).contains(&count),
+            "Can peek between 1 and {MAX_PEEK_BITS} bits, got {count}"
+        );
+
+        self.refill(count)?;
+        Ok(self.bit_buffer >> (u64::BITS - count))
+    }
 
-        self.current_byte <<= 1;
-        self.bits_in_current_byte -= 1;
+    // Drops `count` bits that were previously returned by peek_bits
+    // Fails with UnexpectedEof if the input ended before that many bits were available
+    pub fn consume(&mut self, count: u32) -> std::io::Result<()> {
+        if count > self.bits_in_buffer {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Cannot consume {count} bits, only {} remain",
+                    self.bits_in_buffer
+                ),
+            ));
+        }
 
-        Ok(bit)
+        self.bit_buffer = self.bit_buffer.checked_shl(count).unwrap_or(0);
+        self.bits_in_buffer -= count;
+        Ok(())
     }
 }
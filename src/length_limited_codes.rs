@@ -0,0 +1,114 @@
+use crate::canonical_codes::{
+    build_canonical_tree_with_frequencies, CodeLengths, MAX_CANONICAL_CODE_LENGTH, SYMBOL_COUNT,
+};
+use crate::frequency_map::ByteFrequencyMap;
+use crate::tree_construction::HuffmanNode;
+
+// DEFLATE's limit, short enough for table-driven decoders and 16-bit code registers
+pub const DEFLATE_MAX_CODE_LENGTH: u8 = 15;
+
+// Smallest limit that can hold a code for every one of the 256 byte values
+pub const MIN_CODE_LENGTH_LIMIT: u8 = 8;
+
+// A coin in the package-merge algorithm: its weight and the leaves it is made of
+// Each occurrence of a symbol among the selected coins adds one bit to its code length
+struct Coin {
+    weight: u64,
+    symbols: Vec<u8>,
+}
+
+// Merges the original leaves with the packages built from the previous level
+// Both lists are sorted by weight; on ties leaves come first, which keeps the result stable
+fn merge_coins(leaves: &[(u8, u64)], packages: Vec<Coin>) -> Vec<Coin> {
+    let mut merged = Vec::with_capacity(leaves.len() + packages.len());
+    let mut leaves = leaves.iter().peekable();
+    let mut packages = packages.into_iter().peekable();
+
+    loop {
+        let take_leaf = match (leaves.peek(), packages.peek()) {
+            (Some((_, leaf_weight)), Some(package)) => *leaf_weight <= package.weight,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => return merged,
+        };
+
+        if take_leaf {
+            let &(symbol, weight) = leaves.next().expect("Leaf was peeked");
+            merged.push(Coin {
+                weight,
+                symbols: vec![symbol],
+            });
+        } else {
+            merged.push(packages.next().expect("Package was peeked"));
+        }
+    }
+}
+
+// Pairs up adjacent coins into packages; an odd coin out is discarded
+fn package_coins(coins: Vec<Coin>) -> Vec<Coin> {
+    let mut coins = coins.into_iter();
+    std::iter::from_fn(|| {
+        let first = coins.next()?;
+        let second = coins.next()?;
+        Some(Coin {
+            weight: first.weight + second.weight,
+            symbols: [first.symbols, second.symbols].concat(),
+        })
+    })
+    .collect()
+}
+
+// Computes optimal code lengths that never exceed max_code_length, using package-merge
+// Step 1: Sort the symbols by frequency (then by symbol, for reproducible output)
+// Step 2: Starting from the deepest level, package adjacent coins and merge the packages
+//         with the original leaves, once per allowed bit of code length
+// Step 3: Select the 2n - 2 cheapest coins; a symbol's code length is how often it appears
+// Returns None for an empty map, or when 2^max_code_length cannot cover every symbol
+pub fn length_limited_code_lengths(
+    frequency_map: &ByteFrequencyMap,
+    max_code_length: u8,
+) -> Option<CodeLengths> {
+    let mut leaves: Vec<(u8, u64)> = frequency_map
+        .iter()
+        .map(|(&symbol, &frequency)| (symbol, frequency as u64))
+        .collect();
+    leaves.sort_by_key(|&(symbol, weight)| (weight, symbol));
+
+    let max_code_length = max_code_length.min(MAX_CANONICAL_CODE_LENGTH);
+    if leaves.is_empty() || (leaves.len() as u128) > (1u128 << max_code_length) {
+        return None;
+    }
+
+    let mut lengths = [0u8; SYMBOL_COUNT];
+    if let [(symbol, _)] = leaves.as_slice() {
+        lengths[*symbol as usize] = 1;
+        return Some(lengths);
+    }
+
+    let coins = (1..max_code_length).fold(merge_coins(&leaves, Vec::new()), |coins, _| {
+        merge_coins(&leaves, package_coins(coins))
+    });
+
+    coins
+        .iter()
+        .take(2 * leaves.len() - 2)
+        .flat_map(|coin| coin.symbols.iter())
+        .for_each(|&symbol| lengths[symbol as usize] += 1);
+
+    Some(lengths)
+}
+
+// Builds a Huffman tree whose depth never exceeds max_code_length
+// The tree is the canonical tree for the package-merge code lengths, so it is optimal
+// among all prefix codes respecting the limit, and equal in cost to build_huffman_tree
+// whenever the limit is not binding
+// Returns None for an empty map, or when 2^max_code_length cannot cover every symbol
+pub fn build_length_limited_huffman_tree(
+    frequency_map: &ByteFrequencyMap,
+    max_code_length: u8,
+) -> Option<HuffmanNode> {
+    length_limited_code_lengths(frequency_map, max_code_length).map(|lengths| {
+        build_canonical_tree_with_frequencies(&lengths, frequency_map)
+            .expect("Package-merge always yields a complete prefix code")
+    })
+}
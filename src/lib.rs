@@ -1,22 +1,81 @@
+pub mod adaptive_huffman;
+pub mod block_format;
+pub mod block_index;
+pub mod canonical_codes;
+pub mod checksum;
 pub mod code_extraction;
+pub mod code_length_serialization;
+pub mod coding_method;
 pub mod compression;
 pub mod constants;
+pub mod context_huffman;
+pub mod decoder;
+pub mod decoding_table;
 pub mod decompression;
+pub mod encoder;
+pub mod entropy_coder;
+pub mod error;
+pub mod file_header;
 pub mod frequency_map;
+pub mod frequency_table;
+pub mod huffman_coder;
 pub mod input_bit_stream;
+pub mod length_limited_codes;
 pub mod node_selection;
 pub mod output_bit_stream;
+pub mod random_access;
+pub mod range_coder;
+pub mod run_length_table;
+pub mod tans_coder;
 pub mod tree_construction;
 pub mod tree_deserialization;
 pub mod tree_serialization;
 
-pub use code_extraction::{extract_huffman_codes, HuffmanCodeMap};
-pub use compression::compress;
-pub use decompression::decompress;
-pub use frequency_map::{count_frequencies, ByteFrequencyMap};
+pub use adaptive_huffman::AdaptiveHuffmanModel;
+pub use block_format::{
+    read_block_header, read_block_payload, read_stream_trailer, write_block_header,
+    write_stream_trailer, BlockHeader, StreamTrailer,
+};
+pub use block_index::{
+    read_block_index, read_block_index_entries, write_block_index, BlockIndexEntry,
+};
+pub use canonical_codes::{
+    assign_canonical_codes, build_tree_from_code_lengths, canonical_code_values,
+    code_lengths_from_tree, huffman_code_lengths, CodeLengths,
+};
+pub use checksum::{crc32, Crc32, Crc32Writer};
+pub use code_extraction::{extract_huffman_codes, HuffmanCode, HuffmanCodeTable};
+pub use code_length_serialization::{
+    deserialize_code_lengths, serialize_code_lengths, serialized_code_lengths_bits,
+};
+pub use coding_method::{coding_method, coding_method_named, CodingMethod, CODING_METHODS};
+pub use compression::{compress, compress_stream, compress_with_options, CompressionOptions};
+pub use context_huffman::ContextHuffmanCoder;
+pub use decoder::HuffmanDecoder;
+pub use decoding_table::DecodingTable;
+pub use decompression::{
+    decode_compressed_data, decode_compressed_data_by_tree_walk, decompress, decompress_parallel,
+    decompress_with_options, DecompressionOptions,
+};
+pub use encoder::HuffmanEncoder;
+pub use entropy_coder::{encode_block_with, EntropyCoder};
+pub use error::{HuffmanError, HuffmanResult};
+pub use file_header::{
+    read_file_header, write_file_header, FileHeader, METHOD_ADAPTIVE_HUFFMAN,
+    METHOD_CONTEXT_HUFFMAN, METHOD_HUFFMAN, METHOD_RANGE, METHOD_TANS,
+};
+pub use frequency_map::{count_context_frequencies, count_frequencies, ByteFrequencyMap};
+pub use frequency_table::{
+    deserialize_frequencies, normalize_frequencies, serialize_frequencies, ScaledFrequencies,
+};
+pub use huffman_coder::HuffmanCoder;
 pub use input_bit_stream::InputBitStream;
+pub use length_limited_codes::{build_length_limited_huffman_tree, length_limited_code_lengths};
 pub use node_selection::select_nodes;
 pub use output_bit_stream::OutputBitStream;
+pub use random_access::read_range;
+pub use range_coder::{scale_frequencies, RangeCoder};
+pub use tans_coder::TansCoder;
 pub use tree_construction::{build_huffman_tree, merge_leaf_nodes, merge_nodes, HuffmanNode};
 pub use tree_deserialization::deserialize_tree;
 pub use tree_serialization::{serialize_tree, serialize_tree_to_bits};
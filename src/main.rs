@@ -2,7 +2,10 @@ use clap::{Arg, Command};
 use std::fs::File;
 use std::io::{self, BufReader, BufWriter, Write};
 use std::path::Path;
-use tdd_huffman::{compress, decompress};
+use tdd_huffman::{
+    coding_method_named, compress_with_options, decompress_parallel, read_range,
+    CompressionOptions, DecompressionOptions, CODING_METHODS,
+};
 
 fn main() -> io::Result<()> {
     let cmd = build_cli();
@@ -13,7 +16,22 @@ fn main() -> io::Result<()> {
         }
     };
 
-    if matches.get_flag("compress") {
+    if let Some(extract_matches) = matches.subcommand_matches("extract") {
+        let input_path = extract_matches
+            .get_one::<String>("input")
+            .expect("Input is a required argument");
+        let output_path = extract_matches
+            .get_one::<String>("output")
+            .expect("Output is a required argument");
+        let offset = *extract_matches
+            .get_one::<u64>("offset")
+            .expect("Offset is a required argument");
+        let length = *extract_matches
+            .get_one::<u64>("length")
+            .expect("Length is a required argument");
+
+        extract_range(input_path, output_path, offset, length)?;
+    } else if matches.get_flag("compress") {
         let input_path = matches.get_one::<String>("input").ok_or_else(|| {
             io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -28,7 +46,27 @@ fn main() -> io::Result<()> {
             )
         })?;
 
-        compress_file(input_path, output_path)?;
+        let mut options = CompressionOptions::default();
+        if let Some(&block_size) = matches.get_one::<usize>("block-size") {
+            options.block_size = block_size;
+        }
+        options.max_code_length = matches.get_one::<u8>("max-code-length").copied();
+        if let Some(&threads) = matches.get_one::<usize>("threads") {
+            options.threads = threads;
+        }
+        options.block_index = matches.get_flag("block-index");
+        if let Some(name) = matches.get_one::<String>("method") {
+            options.method = coding_method_named(name)
+                .map(|coding| coding.id)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Unknown coding method {name}"),
+                    )
+                })?;
+        }
+
+        compress_file(input_path, output_path, &options)?;
     } else if matches.get_flag("decompress") {
         let input_path = matches.get_one::<String>("input").ok_or_else(|| {
             io::Error::new(
@@ -44,13 +82,25 @@ fn main() -> io::Result<()> {
             )
         })?;
 
-        decompress_file(input_path, output_path)?;
+        let mut options = DecompressionOptions {
+            max_output_size: matches.get_one::<u64>("max-output-size").copied(),
+            ..Default::default()
+        };
+        if let Some(&threads) = matches.get_one::<usize>("threads") {
+            options.threads = threads;
+        }
+
+        decompress_file(input_path, output_path, &options)?;
     }
 
     Ok(())
 }
 
-fn compress_file(input_path: &str, output_path: &str) -> io::Result<()> {
+fn compress_file(
+    input_path: &str,
+    output_path: &str,
+    options: &CompressionOptions,
+) -> io::Result<()> {
     if !Path::new(input_path).exists() {
         return Err(io::Error::new(
             io::ErrorKind::NotFound,
@@ -68,23 +118,29 @@ fn compress_file(input_path: &str, output_path: &str) -> io::Result<()> {
 
     let input_size = std::fs::metadata(input_path)?.len();
 
-    compress(input_reader, &mut output_writer)?;
+    compress_with_options(input_reader, &mut output_writer, options)?;
 
     output_writer.flush()?;
     drop(output_writer);
 
     let output_size = std::fs::metadata(output_path)?.len();
-    let compression_ratio = output_size as f64 / input_size as f64;
 
     println!("Compression completed!");
     println!("Original size: {input_size} bytes");
     println!("Compressed size: {output_size} bytes");
-    println!("Compression ratio: {compression_ratio:.3}");
+    if input_size > 0 {
+        let compression_ratio = output_size as f64 / input_size as f64;
+        println!("Compression ratio: {compression_ratio:.3}");
+    }
 
     Ok(())
 }
 
-fn decompress_file(input_path: &str, output_path: &str) -> io::Result<()> {
+fn decompress_file(
+    input_path: &str,
+    output_path: &str,
+    options: &DecompressionOptions,
+) -> io::Result<()> {
     if !Path::new(input_path).exists() {
         return Err(io::Error::new(
             io::ErrorKind::NotFound,
@@ -102,7 +158,7 @@ fn decompress_file(input_path: &str, output_path: &str) -> io::Result<()> {
 
     let input_size = std::fs::metadata(input_path)?.len();
 
-    decompress(input_reader, &mut output_writer)?;
+    decompress_parallel(input_reader, &mut output_writer, options)?;
 
     output_writer.flush()?;
     drop(output_writer);
@@ -116,11 +172,63 @@ fn decompress_file(input_path: &str, output_path: &str) -> io::Result<()> {
     Ok(())
 }
 
+fn extract_range(input_path: &str, output_path: &str, offset: u64, length: u64) -> io::Result<()> {
+    if !Path::new(input_path).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Input file '{input_path}' not found"),
+        ));
+    }
+
+    let input_reader = BufReader::new(File::open(input_path)?);
+
+    println!(
+        "Extracting {length} bytes at offset {offset} from '{input_path}' to '{output_path}'..."
+    );
+
+    let extracted = read_range(input_reader, offset, length)?;
+    std::fs::write(output_path, &extracted)?;
+
+    println!("Extraction completed!");
+    println!("Extracted size: {} bytes", extracted.len());
+
+    Ok(())
+}
+
 fn build_cli() -> Command {
     Command::new("huffman")
         .version("1.0")
         .about("A Huffman compression/decompression utility")
         .arg_required_else_help(true)
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("extract")
+                .about("Decompress only a byte range of a compressed file")
+                .arg(Arg::new("input").help("Input file").required(true).index(1))
+                .arg(
+                    Arg::new("offset")
+                        .long("offset")
+                        .value_name("BYTES")
+                        .value_parser(clap::value_parser!(u64))
+                        .required(true)
+                        .help("Position of the first byte to extract in the original data"),
+                )
+                .arg(
+                    Arg::new("length")
+                        .long("length")
+                        .value_name("BYTES")
+                        .value_parser(clap::value_parser!(u64))
+                        .required(true)
+                        .help("Number of bytes to extract"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Output file")
+                        .required(true),
+                ),
+        )
         .arg(
             Arg::new("compress")
                 .short('c')
@@ -135,6 +243,51 @@ fn build_cli() -> Command {
                 .action(clap::ArgAction::SetTrue)
                 .help("Decompress the input file"),
         )
+        .arg(
+            Arg::new("block-size")
+                .long("block-size")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(usize))
+                .help("Size of the blocks that get their own Huffman tree when compressing"),
+        )
+        .arg(
+            Arg::new("max-code-length")
+                .long("max-code-length")
+                .value_name("BITS")
+                .value_parser(clap::value_parser!(u8))
+                .help("Limit Huffman codes to this many bits (8 to 64) when compressing"),
+        )
+        .arg(
+            Arg::new("method")
+                .long("method")
+                .value_name("METHOD")
+                .value_parser(clap::builder::PossibleValuesParser::new(
+                    CODING_METHODS.iter().map(|coding| coding.name),
+                ))
+                .help("Coding method when compressing: huffman (default), adaptive, range, tans or context"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .help("Encode or decode this many blocks in parallel (default 1)"),
+        )
+        .arg(
+            Arg::new("block-index")
+                .long("block-index")
+                .action(clap::ArgAction::SetTrue)
+                .help(
+                    "Append a block index when compressing, so the file can be decoded in parallel",
+                ),
+        )
+        .arg(
+            Arg::new("max-output-size")
+                .long("max-output-size")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(u64))
+                .help("Refuse to decompress files that would expand beyond this many bytes"),
+        )
         .arg(Arg::new("input").help("Input file").required(true).index(1))
         .arg(
             Arg::new("output")
@@ -1,58 +1,77 @@
 use crate::constants::BITS_PER_BYTE;
 
+// Most bits a single accumulator update can take without overflowing the u64,
+// given that up to 7 bits of an unfinished byte may already be pending
+const MAX_BITS_PER_UPDATE: u32 = u64::BITS - BITS_PER_BYTE as u32;
+
 pub struct OutputBitStream<W> {
     writer: W,
-    current_byte: u8,
-    bits_in_current_byte: usize,
+    // Pending bits, aligned to the most significant end; fewer than 8 between calls
+    bit_buffer: u64,
+    bits_in_buffer: u32,
 }
 
 impl<W: std::io::Write> OutputBitStream<W> {
     // Creates a new bit stream writer that wraps any byte-oriented writer
-    // Initializes with an empty current byte and zero bits accumulated
+    // Initializes with an empty bit buffer
     pub fn new(writer: W) -> Self {
         Self {
             writer,
-            current_byte: 0,
-            bits_in_current_byte: 0,
+            bit_buffer: 0,
+            bits_in_buffer: 0,
         }
     }
 
-    // Writes the current accumulated byte to the underlying writer
-    // Resets the current byte to 0 and bit counter to 0
-    // Called automatically when 8 bits have been accumulated
-    fn emit_current_byte(&mut self) -> std::io::Result<()> {
-        self.writer.write_all(&[self.current_byte])?;
-        self.current_byte = 0;
-        self.bits_in_current_byte = 0;
+    // Writes every complete byte in the bit buffer to the underlying writer at once
+    // Keeps the remaining 0-7 bits of an unfinished byte in the buffer
+    fn emit_complete_bytes(&mut self) -> std::io::Result<()> {
+        let byte_count = (self.bits_in_buffer / BITS_PER_BYTE as u32) as usize;
+        if byte_count == 0 {
+            return Ok(());
+        }
+
+        self.writer
+            .write_all(&self.bit_buffer.to_be_bytes()[..byte_count])?;
+        let emitted_bits = (byte_count * BITS_PER_BYTE) as u32;
+        self.bit_buffer = self.bit_buffer.checked_shl(emitted_bits).unwrap_or(0);
+        self.bits_in_buffer -= emitted_bits;
         Ok(())
     }
 
     // Writes a single bit (0 or 1) to the stream
-    // Accumulates bits into a byte buffer: sets the bit at the correct position
     // When 8 bits are accumulated, automatically writes the complete byte
-    // Uses bitwise OR to set the bit at the calculated position within the byte
     pub fn write_bit(&mut self, bit: u8) -> std::io::Result<()> {
         debug_assert!(bit <= 1, "Bit value must be 0 or 1, got: {bit}");
+        self.write_bits(bit as u64, 1)
+    }
 
-        if bit == 1 {
-            self.current_byte |= 1 << (BITS_PER_BYTE - 1 - self.bits_in_current_byte);
-        }
-
-        self.bits_in_current_byte += 1;
+    // Writes the lowest `count` bits of value (at most 64), most significant first
+    // Appends them to the bit buffer below the pending bits, then writes out
+    // all complete bytes, so a whole code costs one buffer update instead of one per bit
+    pub fn write_bits(&mut self, value: u64, count: u32) -> std::io::Result<()> {
+        debug_assert!(count <= u64::BITS, "Can write at most 64 bits, got {count}");
 
-        if self.bits_in_current_byte == BITS_PER_BYTE {
-            self.emit_current_byte()?;
+        if count > MAX_BITS_PER_UPDATE {
+            self.write_bits(value >> u32::BITS, count - u32::BITS)?;
+            return self.write_bits(value & u32::MAX as u64, u32::BITS);
+        }
+        if count == 0 {
+            return Ok(());
         }
 
-        Ok(())
+        let masked = value & (u64::MAX >> (u64::BITS - count));
+        self.bit_buffer |= masked << (u64::BITS - self.bits_in_buffer - count);
+        self.bits_in_buffer += count;
+        self.emit_complete_bytes()
     }
 
     // Writes any remaining bits as a final byte
     // If there are leftover bits (1-7), they are padded with zeros to complete the byte
     // Must be called at the end to ensure all bits are written to the output
     pub fn flush(&mut self) -> std::io::Result<()> {
-        if self.bits_in_current_byte > 0 {
-            self.emit_current_byte()?;
+        if self.bits_in_buffer > 0 {
+            self.bits_in_buffer = BITS_PER_BYTE as u32;
+            self.emit_complete_bytes()?;
         }
         Ok(())
     }
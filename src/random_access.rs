@@ -0,0 +1,81 @@
+use crate::block_format::{read_block_header, read_block_payload};
+use crate::block_index::{read_block_index, BlockIndexEntry};
+use crate::decoder::decode_block_prefix;
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::file_header::{read_file_header, FILE_HEADER_SIZE};
+use std::io::{Read, Seek, SeekFrom};
+
+// Decompresses only the bytes offset..offset + length of the original data
+// Step 1: Find every block's offset, from the block index if the stream has one
+// Step 2: Skip the blocks that end before the range without reading their payloads
+// Step 3: Decode the overlapping blocks, stopping each at the end of the range
+// Step 4: Stop at the first block that reaches past the end of the range
+// A range running past the end of the data is cut short, like a read near end of file
+// Only the decoded blocks are checked: the trailer's CRC-32 covers the whole stream and
+// cannot be verified without decoding all of it
+pub fn read_range<R: Read + Seek>(
+    mut input_reader: R,
+    offset: u64,
+    length: u64,
+) -> HuffmanResult<Vec<u8>> {
+    let start = input_reader.stream_position()?;
+    let header = read_file_header(&mut input_reader)?;
+    let entries = if header.has_block_index() {
+        read_block_index(&mut input_reader, start)?
+    } else {
+        scan_block_offsets(&mut input_reader, start)?
+    };
+
+    let end = offset.saturating_add(length);
+    let mut output = Vec::new();
+    let mut block_start = 0u64;
+
+    for entry in entries {
+        let block_end = block_start + entry.original_length as u64;
+        if block_end > offset {
+            input_reader.seek(SeekFrom::Start(start + entry.offset))?;
+            let block_header = read_block_header(&mut input_reader)?;
+            if block_header.is_end() || block_header.original_length != entry.original_length {
+                return Err(HuffmanError::CorruptIndex(format!(
+                    "Entry for offset {} does not match the block found there",
+                    entry.offset
+                )));
+            }
+            let payload = read_block_payload(&mut input_reader, &block_header)?;
+            let needed = (end.min(block_end) - block_start) as usize;
+            let decoded = decode_block_prefix(header.method, &block_header, payload, needed)?;
+            let skipped = offset.saturating_sub(block_start) as usize;
+            output.extend_from_slice(&decoded[skipped..]);
+        }
+        if block_end >= end {
+            break;
+        }
+        block_start = block_end;
+    }
+
+    Ok(output)
+}
+
+// Lists the blocks of a stream without an index by hopping from one block header to the next
+// Payloads are skipped with a seek, so this costs one small read per block
+fn scan_block_offsets<R: Read + Seek>(
+    input_reader: &mut R,
+    start: u64,
+) -> HuffmanResult<Vec<BlockIndexEntry>> {
+    let mut entries = Vec::new();
+    let mut offset = FILE_HEADER_SIZE;
+    input_reader.seek(SeekFrom::Start(start + offset))?;
+
+    loop {
+        let block_header = read_block_header(input_reader)?;
+        if block_header.is_end() {
+            return Ok(entries);
+        }
+        entries.push(BlockIndexEntry {
+            offset,
+            original_length: block_header.original_length,
+        });
+        offset += block_header.encoded_length();
+        input_reader.seek(SeekFrom::Start(start + offset))?;
+    }
+}
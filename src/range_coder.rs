@@ -0,0 +1,184 @@
+use crate::block_format::BLOCK_TYPE_RANGE;
+use crate::canonical_codes::SYMBOL_COUNT;
+use crate::compression::CompressionOptions;
+use crate::entropy_coder::EntropyCoder;
+use crate::error::HuffmanResult;
+use crate::file_header::METHOD_RANGE;
+use crate::frequency_map::ByteFrequencyMap;
+use crate::frequency_table::{
+    deserialize_frequencies, normalize_frequencies, serialize_frequencies, ScaledFrequencies,
+};
+use crate::input_bit_stream::InputBitStream;
+use crate::output_bit_stream::OutputBitStream;
+use std::io::{Read, Write};
+
+// Scaled frequencies of a block always add up to 2^SCALE_BITS
+pub const SCALE_BITS: u32 = 16;
+const TOTAL_FREQUENCY: u32 = 1 << SCALE_BITS;
+
+// The range is renormalized whenever it drops below 2^24, one byte at a time
+const TOP: u32 = 1 << 24;
+// Bytes the decoder reads before its first symbol, and the encoder flushes after its last
+const CODE_BYTES: usize = 5;
+
+// Scales the counts of a block so that they add up to 2^SCALE_BITS
+// Returns None for an empty map
+pub fn scale_frequencies(frequency_map: &ByteFrequencyMap) -> Option<ScaledFrequencies> {
+    normalize_frequencies(frequency_map, SCALE_BITS)
+}
+
+// Range coder over the scaled frequencies of one block
+// Each symbol narrows the range to its share of the current range, so a symbol with
+// probability p costs close to -log2(p) bits, well under one bit for very common symbols
+// The encoder follows LZMA's: low is kept in 33 bits so that a carry out of the top byte can
+// still be added to the bytes already waiting in the cache
+#[derive(Debug, Clone)]
+pub struct RangeCoder {
+    frequencies: ScaledFrequencies,
+    // Sum of the frequencies of all smaller symbols, with the total at the end
+    cumulative: [u32; SYMBOL_COUNT + 1],
+    range: u32,
+    // Encoder state: the low end of the range, the pending byte and how many bytes wait
+    low: u64,
+    cache: u8,
+    cache_size: u64,
+    // Decoder state: the code read so far, once the first bytes have been read
+    code: Option<u32>,
+}
+
+impl RangeCoder {
+    // Creates a coder for the given frequencies, which must add up to 2^SCALE_BITS
+    pub fn new(frequencies: ScaledFrequencies) -> Self {
+        let mut cumulative = [0u32; SYMBOL_COUNT + 1];
+        (0..SYMBOL_COUNT).for_each(|symbol| {
+            cumulative[symbol + 1] = cumulative[symbol] + frequencies[symbol];
+        });
+        Self {
+            frequencies,
+            cumulative,
+            range: u32::MAX,
+            low: 0,
+            cache: 0,
+            cache_size: 1,
+            code: None,
+        }
+    }
+
+    // Returns the scaled frequencies
+    pub fn frequencies(&self) -> &ScaledFrequencies {
+        &self.frequencies
+    }
+
+    // Moves the top byte of low out to the stream
+    // A byte is held back while it could still be raised by a carry; a run of 0xFF bytes
+    // behind it is held back too, since a carry would turn them all into 0x00
+    fn shift_low<W: Write>(&mut self, bit_stream: &mut OutputBitStream<W>) -> std::io::Result<()> {
+        if (self.low as u32) < 0xFF00_0000 || self.low >> 32 != 0 {
+            let carry = (self.low >> 32) as u8;
+            let mut pending = self.cache;
+            while self.cache_size > 0 {
+                bit_stream.write_bits(pending.wrapping_add(carry) as u64, u8::BITS)?;
+                pending = 0xFF;
+                self.cache_size -= 1;
+            }
+            self.cache = (self.low >> 24) as u8;
+        }
+        self.cache_size += 1;
+        self.low = (self.low & 0x00FF_FFFF) << 8;
+        Ok(())
+    }
+
+    // Writes out every byte still needed to tell the final range apart
+    fn flush<W: Write>(&mut self, bit_stream: &mut OutputBitStream<W>) -> std::io::Result<()> {
+        (0..CODE_BYTES).try_for_each(|_| self.shift_low(bit_stream))
+    }
+
+    // Reads the first bytes of the code on the first decoded symbol
+    fn code<R: Read>(&mut self, bit_stream: &mut InputBitStream<R>) -> HuffmanResult<u32> {
+        match self.code {
+            Some(code) => Ok(code),
+            None => (0..CODE_BYTES).try_fold(0u32, |code, _| {
+                Ok(code << 8 | bit_stream.read_bits(u8::BITS)? as u32)
+            }),
+        }
+    }
+}
+
+impl EntropyCoder for RangeCoder {
+    const METHOD: u8 = METHOD_RANGE;
+    const BLOCK_TYPE: u8 = BLOCK_TYPE_RANGE;
+
+    fn from_frequencies(
+        frequency_map: &ByteFrequencyMap,
+        _options: &CompressionOptions,
+    ) -> Option<Self> {
+        scale_frequencies(frequency_map).map(Self::new)
+    }
+
+    fn serialize_model<W: Write>(
+        &self,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        serialize_frequencies(&self.frequencies, bit_stream)
+    }
+
+    fn deserialize_model<R: Read>(bit_stream: &mut InputBitStream<R>) -> HuffmanResult<Self> {
+        deserialize_frequencies(bit_stream, SCALE_BITS).map(Self::new)
+    }
+
+    // Narrows the range to the symbol's share and writes out the bytes that are settled
+    // Nothing is final until encode_symbols flushes the coder at the end of the block
+    fn encode_symbol<W: Write>(
+        &mut self,
+        symbol: u8,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        let share = self.range >> SCALE_BITS;
+        self.low += share as u64 * self.cumulative[symbol as usize] as u64;
+        self.range = share * self.frequencies[symbol as usize];
+        while self.range < TOP {
+            self.range <<= 8;
+            self.shift_low(bit_stream)?;
+        }
+        Ok(())
+    }
+
+    // Finds the symbol whose share holds the code, then narrows the range the same way
+    // the encoder did; the code may point past the last share only in corrupt data
+    fn decode_symbol<R: Read>(&mut self, bit_stream: &mut InputBitStream<R>) -> HuffmanResult<u8> {
+        let mut code = self.code(bit_stream)?;
+        let share = self.range >> SCALE_BITS;
+        let target = (code / share).min(TOTAL_FREQUENCY - 1);
+        let symbol = self.cumulative.partition_point(|&start| start <= target) - 1;
+
+        code -= share * self.cumulative[symbol];
+        self.range = share * self.frequencies[symbol];
+        while self.range < TOP {
+            self.range <<= 8;
+            code = code << 8 | bit_stream.read_bits(u8::BITS)? as u32;
+        }
+        self.code = Some(code);
+        Ok(symbol as u8)
+    }
+
+    fn encode_symbols<W: Write>(
+        &mut self,
+        symbols: &[u8],
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        symbols
+            .iter()
+            .try_for_each(|&symbol| self.encode_symbol(symbol, bit_stream))?;
+        self.flush(bit_stream)
+    }
+
+    // A symbol with the largest frequency m still costs log2(2^16 / m) bits, which is at
+    // least (2^16 - m) / 2^16; a block holding a single symbol costs nothing at all
+    fn max_symbols(&self, payload_length: usize) -> Option<u64> {
+        let largest = self.frequencies.iter().copied().max().unwrap_or(0);
+        let smallest_cost = (TOTAL_FREQUENCY - largest) as u64;
+        (smallest_cost > 0).then(|| {
+            (payload_length as u64 * 8).saturating_mul(TOTAL_FREQUENCY as u64) / smallest_cost
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use crate::canonical_codes::SYMBOL_COUNT;
+use crate::error::{HuffmanError, HuffmanResult};
+use crate::input_bit_stream::InputBitStream;
+use crate::output_bit_stream::OutputBitStream;
+use std::io::{Read, Write};
+
+const ZERO_RUN_BIT: u8 = 0;
+const LITERAL_BIT: u8 = 1;
+
+const WIDTH_FIELD_BITS: u32 = 4;
+const RUN_LENGTH_BITS: u32 = 8;
+
+// One value per byte value, zero meaning the symbol is absent
+pub type SymbolValues = [u64; SYMBOL_COUNT];
+
+// Bit width of the largest stored value (value - bias) among the present symbols, at least 1
+fn value_width(values: &SymbolValues, bias: u64) -> u32 {
+    let largest = values
+        .iter()
+        .filter(|&&value| value > 0)
+        .map(|&value| value - bias)
+        .max()
+        .unwrap_or(0);
+    (u64::BITS - largest.leading_zeros()).max(1)
+}
+
+// Serializes a value for every byte value in a compact run-length form
+// Shared by code length tables and scaled frequency tables, which differ only in the bias
+// subtracted from present values (a frequency is never 0, so it is stored minus 1)
+// First 4 bits: the bit width used for each stored value, minus one
+// Then, walking the symbols in order:
+// - '0' bit followed by 8 bits (run - 1) for a run of up to 256 absent symbols
+// - '1' bit followed by (value - bias) in `width` bits for a present symbol
+pub(crate) fn write_run_length_table<W: Write>(
+    values: &SymbolValues,
+    bias: u64,
+    bit_stream: &mut OutputBitStream<W>,
+) -> std::io::Result<()> {
+    let width = value_width(values, bias);
+    bit_stream.write_bits(width as u64 - 1, WIDTH_FIELD_BITS)?;
+
+    let mut symbol = 0;
+    while symbol < SYMBOL_COUNT {
+        if values[symbol] == 0 {
+            let run = values[symbol..]
+                .iter()
+                .take_while(|&&value| value == 0)
+                .count();
+            bit_stream.write_bit(ZERO_RUN_BIT)?;
+            bit_stream.write_bits(run as u64 - 1, RUN_LENGTH_BITS)?;
+            symbol += run;
+        } else {
+            bit_stream.write_bit(LITERAL_BIT)?;
+            bit_stream.write_bits(values[symbol] - bias, width)?;
+            symbol += 1;
+        }
+    }
+
+    Ok(())
+}
+
+// Number of bits write_run_length_table writes for the values, without writing them
+pub(crate) fn run_length_table_bits(values: &SymbolValues, bias: u64) -> usize {
+    let width = value_width(values, bias) as usize;
+    let literal_bits = values.iter().filter(|&&value| value > 0).count() * (1 + width);
+    // Every zero run starts either at the first symbol or right after a present one
+    let zero_runs = (0..SYMBOL_COUNT)
+        .filter(|&symbol| values[symbol] == 0 && (symbol == 0 || values[symbol - 1] > 0))
+        .count();
+    WIDTH_FIELD_BITS as usize + literal_bits + zero_runs * (1 + RUN_LENGTH_BITS as usize)
+}
+
+// Fewest bits any table with this many present symbols can take: values one bit wide and
+// all the absent symbols in a single run
+pub(crate) fn least_run_length_table_bits(present: usize) -> usize {
+    let zero_runs = (present < SYMBOL_COUNT) as usize;
+    WIDTH_FIELD_BITS as usize + present * 2 + zero_runs * (1 + RUN_LENGTH_BITS as usize)
+}
+
+// Reads back values written by write_run_length_table with the same bias
+// Widths above max_width and runs that would run past the last symbol are rejected as a
+// corrupt table
+pub(crate) fn read_run_length_table<R: Read>(
+    bit_stream: &mut InputBitStream<R>,
+    bias: u64,
+    max_width: u32,
+) -> HuffmanResult<SymbolValues> {
+    let width = bit_stream.read_bits(WIDTH_FIELD_BITS)? as u32 + 1;
+    if width > max_width {
+        return Err(HuffmanError::CorruptTree(format!(
+            "Invalid value width: {width}"
+        )));
+    }
+
+    let mut values = [0u64; SYMBOL_COUNT];
+    let mut symbol = 0;
+    while symbol < SYMBOL_COUNT {
+        match bit_stream.read_bit()? {
+            ZERO_RUN_BIT => {
+                let run = bit_stream.read_bits(RUN_LENGTH_BITS)? as usize + 1;
+                if symbol + run > SYMBOL_COUNT {
+                    return Err(HuffmanError::CorruptTree(format!(
+                        "Zero run of {run} at symbol {symbol} overruns the table"
+                    )));
+                }
+                symbol += run;
+            }
+            _ => {
+                values[symbol] = bit_stream.read_bits(width)? + bias;
+                symbol += 1;
+            }
+        }
+    }
+
+    Ok(values)
+}
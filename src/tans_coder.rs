@@ -0,0 +1,263 @@
+use crate::block_format::BLOCK_TYPE_TANS;
+use crate::canonical_codes::SYMBOL_COUNT;
+use crate::compression::CompressionOptions;
+use crate::entropy_coder::EntropyCoder;
+use crate::error::HuffmanResult;
+use crate::file_header::METHOD_TANS;
+use crate::frequency_map::ByteFrequencyMap;
+use crate::frequency_table::{
+    deserialize_frequencies, normalize_frequencies, serialize_frequencies, ScaledFrequencies,
+};
+use crate::input_bit_stream::InputBitStream;
+use crate::output_bit_stream::OutputBitStream;
+use std::io::{Read, Write};
+
+// Normalized frequencies of a block add up to the table size, 2^TABLE_LOG
+pub const TABLE_LOG: u32 = 12;
+const TABLE_SIZE: usize = 1 << TABLE_LOG;
+
+// One decoder state: the symbol it stands for, and how to reach the next state
+// The next state is base plus the next `bits` bits read from the stream
+#[derive(Debug, Clone, Copy)]
+struct DecodeEntry {
+    symbol: u8,
+    bits: u8,
+    base: u16,
+}
+
+// Table-based asymmetric numeral system coder (tANS), the scheme of zstd's FSE
+// Each symbol owns as many of the 2^TABLE_LOG states as its normalized frequency, and coding
+// one moves the state into that set after shifting out just enough bits, so a symbol with
+// probability p costs close to -log2(p) bits with nothing but table lookups and shifts
+// The encoder has to run backwards over the block for the decoder to run forwards, so it
+// packs the bits of the whole block into a ReversedBits buffer and writes them once the final
+// state is known
+#[derive(Debug, Clone)]
+pub struct TansCoder {
+    frequencies: ScaledFrequencies,
+    // Sum of the frequencies of all smaller symbols
+    cumulative: [u32; SYMBOL_COUNT],
+    // Encoder: for each symbol, its states in table order (offset by TABLE_SIZE)
+    encode_states: Vec<u16>,
+    // Decoder: one entry per state
+    decode_table: Vec<DecodeEntry>,
+    // Encoder: symbols waiting for encode_symbols to finish the block
+    pending: Vec<u8>,
+    // Decoder: the current state, once it has been read
+    state: Option<usize>,
+}
+
+// Bits produced last to first and written first to last, packed 64 to a word
+// Prepending a chunk to the output is appending its bits, least significant first, to the
+// reversed output; reading the words back from the end then yields the output in order
+#[derive(Debug, Default)]
+struct ReversedBits {
+    words: Vec<u64>,
+    // Bits used in the last word
+    used: u32,
+}
+
+impl ReversedBits {
+    // Puts a value of `count` bits (fewer than 64) in front of everything pushed so far
+    fn push_front(&mut self, value: u64, count: u32) {
+        if count == 0 {
+            return;
+        }
+        if self.words.is_empty() || self.used == u64::BITS {
+            self.words.push(0);
+            self.used = 0;
+        }
+        let last = self.words.len() - 1;
+        self.words[last] |= value << self.used;
+        let spilled = (self.used + count).saturating_sub(u64::BITS);
+        if spilled > 0 {
+            self.words.push(value >> (count - spilled));
+        }
+        self.used = match spilled {
+            0 => self.used + count,
+            _ => spilled,
+        };
+    }
+
+    // Writes the bits in output order: the partly used last word first, most significant
+    // bit first, then every full word down to the first
+    fn write_to<W: Write>(&self, bit_stream: &mut OutputBitStream<W>) -> std::io::Result<()> {
+        self.words
+            .iter()
+            .rev()
+            .enumerate()
+            .try_for_each(|(index, &word)| match index {
+                0 => bit_stream.write_bits(word, self.used),
+                _ => bit_stream.write_bits(word, u64::BITS),
+            })
+    }
+}
+
+// Spreads the symbols over the table the way FSE does: stepping by a fixed odd stride that
+// is coprime with the table size, so every position is visited once and each symbol's
+// states end up scattered rather than bunched together
+// Returns the symbol of every state
+pub fn spread_symbols(frequencies: &ScaledFrequencies) -> Vec<u8> {
+    let step = (TABLE_SIZE >> 1) + (TABLE_SIZE >> 3) + 3;
+    let mut table = vec![0u8; TABLE_SIZE];
+    let mut position = 0;
+    frequencies
+        .iter()
+        .enumerate()
+        .for_each(|(symbol, &frequency)| {
+            (0..frequency).for_each(|_| {
+                table[position] = symbol as u8;
+                position = (position + step) & (TABLE_SIZE - 1);
+            });
+        });
+    table
+}
+
+impl TansCoder {
+    // Creates a coder for the given frequencies, which must add up to 2^TABLE_LOG
+    // Step 1: Spread the symbols over the states
+    // Step 2: Number the states of each symbol in table order; the n-th state of a symbol
+    //         with frequency f is reached from the encoder states that shift down to f + n
+    // Step 3: Give each decoder state the bits that take f + n back up into the table
+    pub fn new(frequencies: ScaledFrequencies) -> Self {
+        let mut cumulative = [0u32; SYMBOL_COUNT];
+        (1..SYMBOL_COUNT).for_each(|symbol| {
+            cumulative[symbol] = cumulative[symbol - 1] + frequencies[symbol - 1];
+        });
+
+        let spread = spread_symbols(&frequencies);
+        let mut seen = [0u32; SYMBOL_COUNT];
+        let mut encode_states = vec![0u16; TABLE_SIZE];
+        let decode_table = spread
+            .iter()
+            .enumerate()
+            .map(|(state, &symbol)| {
+                let symbol_index = symbol as usize;
+                let occurrence = seen[symbol_index];
+                seen[symbol_index] += 1;
+                encode_states[(cumulative[symbol_index] + occurrence) as usize] =
+                    (TABLE_SIZE + state) as u16;
+
+                let next = frequencies[symbol_index] + occurrence;
+                let bits = TABLE_LOG - next.ilog2();
+                DecodeEntry {
+                    symbol,
+                    bits: bits as u8,
+                    base: ((next << bits) as usize - TABLE_SIZE) as u16,
+                }
+            })
+            .collect();
+
+        Self {
+            frequencies,
+            cumulative,
+            encode_states,
+            decode_table,
+            pending: Vec::new(),
+            state: None,
+        }
+    }
+
+    // Returns the normalized frequencies
+    pub fn frequencies(&self) -> &ScaledFrequencies {
+        &self.frequencies
+    }
+
+    // Returns the symbol of a decoder state (0 to TABLE_SIZE), how many bits it reads and
+    // the base those bits are added to for the next state
+    pub fn decode_entry(&self, state: usize) -> (u8, u32, usize) {
+        let entry = self.decode_table[state];
+        (entry.symbol, entry.bits as u32, entry.base as usize)
+    }
+
+    // Moves the encoder state (kept between TABLE_SIZE and twice that) past one symbol
+    // Returns the new state and the low bits shifted out of the old one, with their count
+    // The shift is the one that brings the state into [f, 2f) for the symbol's frequency f
+    pub fn encode_step(&self, state: usize, symbol: u8) -> (usize, u64, u32) {
+        let frequency = self.frequencies[symbol as usize];
+        let mut bits = TABLE_LOG - frequency.ilog2();
+        if ((state >> bits) as u32) < frequency {
+            bits -= 1;
+        }
+        let low_bits = (state & ((1 << bits) - 1)) as u64;
+        let index = self.cumulative[symbol as usize] + (state >> bits) as u32 - frequency;
+        (self.encode_states[index as usize] as usize, low_bits, bits)
+    }
+}
+
+impl EntropyCoder for TansCoder {
+    const METHOD: u8 = METHOD_TANS;
+    const BLOCK_TYPE: u8 = BLOCK_TYPE_TANS;
+
+    fn from_frequencies(
+        frequency_map: &ByteFrequencyMap,
+        _options: &CompressionOptions,
+    ) -> Option<Self> {
+        normalize_frequencies(frequency_map, TABLE_LOG).map(Self::new)
+    }
+
+    fn serialize_model<W: Write>(
+        &self,
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        serialize_frequencies(&self.frequencies, bit_stream)
+    }
+
+    fn deserialize_model<R: Read>(bit_stream: &mut InputBitStream<R>) -> HuffmanResult<Self> {
+        deserialize_frequencies(bit_stream, TABLE_LOG).map(Self::new)
+    }
+
+    // Holds the symbol back: nothing can be written until the whole block is known
+    fn encode_symbol<W: Write>(
+        &mut self,
+        symbol: u8,
+        _bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        self.pending.push(symbol);
+        Ok(())
+    }
+
+    // Reads the state on the first symbol, then follows the table one state at a time
+    fn decode_symbol<R: Read>(&mut self, bit_stream: &mut InputBitStream<R>) -> HuffmanResult<u8> {
+        let state = match self.state {
+            Some(state) => state,
+            None => bit_stream.read_bits(TABLE_LOG)? as usize,
+        };
+        let entry = self.decode_table[state];
+        let next = entry.base as usize + bit_stream.read_bits(entry.bits as u32)? as usize;
+        self.state = Some(next);
+        Ok(entry.symbol)
+    }
+
+    // Encodes the block from its last symbol to its first, then writes the final state
+    // followed by the shifted-out bits in the order the decoder will ask for them
+    // Symbols held back by encode_symbol come before the given ones
+    fn encode_symbols<W: Write>(
+        &mut self,
+        symbols: &[u8],
+        bit_stream: &mut OutputBitStream<W>,
+    ) -> std::io::Result<()> {
+        let pending = std::mem::take(&mut self.pending);
+        let mut bits = ReversedBits::default();
+        let final_state = pending
+            .iter()
+            .chain(symbols)
+            .rev()
+            .fold(TABLE_SIZE, |state, &symbol| {
+                let (next, low_bits, count) = self.encode_step(state, symbol);
+                bits.push_front(low_bits, count);
+                next
+            });
+
+        bit_stream.write_bits((final_state - TABLE_SIZE) as u64, TABLE_LOG)?;
+        bits.write_to(bit_stream)
+    }
+
+    // While no frequency is over half the table every state reads at least one bit, so the
+    // default bound holds; a more common symbol can step between states without reading any
+    // bits at all, and then the payload does not bound how many symbols it decodes to
+    fn max_symbols(&self, payload_length: usize) -> Option<u64> {
+        let largest = self.frequencies.iter().copied().max().unwrap_or(0);
+        (largest as usize <= TABLE_SIZE / 2).then_some(payload_length as u64 * 8)
+    }
+}
@@ -3,10 +3,12 @@ use crate::node_selection::SymbolFrequency;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct HuffmanNode {
     frequency: usize,
     symbol: Option<u8>,
+    // Smallest symbol anywhere in this subtree, used to break frequency ties
+    lowest_symbol: u8,
     left_child: Option<Box<HuffmanNode>>,
     right_child: Option<Box<HuffmanNode>>,
 }
@@ -17,6 +19,7 @@ impl HuffmanNode {
         Self {
             frequency,
             symbol: Some(symbol),
+            lowest_symbol: symbol,
             left_child: None,
             right_child: None,
         }
@@ -28,6 +31,7 @@ impl HuffmanNode {
         Self {
             frequency: left_child.frequency + right_child.frequency,
             symbol: None,
+            lowest_symbol: left_child.lowest_symbol.min(right_child.lowest_symbol),
             left_child: Some(Box::new(left_child)),
             right_child: Some(Box::new(right_child)),
         }
@@ -67,8 +71,13 @@ impl HuffmanNode {
 impl Ord for HuffmanNode {
     // Compares nodes by frequency for priority queue ordering
     // Lower frequencies have higher priority (reversed comparison)
+    // Equal frequencies are ordered by the lowest symbol each subtree contains, so the
+    // merge order, and with it the compressed output, never depends on hash map order
     fn cmp(&self, other: &Self) -> Ordering {
-        other.frequency.cmp(&self.frequency)
+        other
+            .frequency
+            .cmp(&self.frequency)
+            .then_with(|| other.lowest_symbol.cmp(&self.lowest_symbol))
     }
 }
 
@@ -121,10 +130,12 @@ fn build_tree_from_heap(mut heap: BinaryHeap<HuffmanNode>) -> HuffmanNode {
 // Step 1: Convert each (byte, frequency) pair into a leaf node
 // Step 2: Put all leaf nodes into a priority queue (heap)
 // Step 3: Repeatedly merge the two lowest-frequency nodes until one remains
+// Ties are broken by lowest symbol, so the same frequencies always build the same tree
 // The resulting tree assigns shorter codes to more frequent bytes
-pub fn build_huffman_tree(frequency_map: &ByteFrequencyMap) -> HuffmanNode {
+// Returns None for an empty frequency map, since there is nothing to build a tree from
+pub fn build_huffman_tree(frequency_map: &ByteFrequencyMap) -> Option<HuffmanNode> {
     if frequency_map.is_empty() {
-        panic!("Cannot build Huffman tree from empty frequency map");
+        return None;
     }
 
     let heap: BinaryHeap<HuffmanNode> = frequency_map
@@ -132,5 +143,5 @@ pub fn build_huffman_tree(frequency_map: &ByteFrequencyMap) -> HuffmanNode {
         .map(|(symbol, frequency)| HuffmanNode::new_leaf(*symbol, *frequency))
         .collect();
 
-    build_tree_from_heap(heap)
+    Some(build_tree_from_heap(heap))
 }
@@ -1,37 +1,66 @@
+use crate::canonical_codes::{MAX_CANONICAL_CODE_LENGTH, SYMBOL_COUNT};
 use crate::constants::BITS_PER_BYTE;
+use crate::error::{HuffmanError, HuffmanResult};
 use crate::input_bit_stream::InputBitStream;
 use crate::tree_construction::HuffmanNode;
 
 const LEAF_NODE_BIT: u8 = 1;
 const INTERNAL_NODE_BIT: u8 = 0;
 
+// Deepest tree accepted from a stream; the encoder never writes codes longer than this,
+// and the bound keeps the recursion from overflowing the stack on a run of '0' bits
+pub const MAX_TREE_DEPTH: u32 = MAX_CANONICAL_CODE_LENGTH as u32;
+
+// Symbols already seen while reading a tree; a second leaf for the same symbol is corrupt
+type SeenSymbols = [bool; SYMBOL_COUNT];
+
 // Reconstructs a Huffman tree from its binary representation
 // Reads the first bit to determine node type:
 // - '1' bit means leaf node (followed by 8 symbol bits)
 // - '0' bit means internal node (followed by left and right child trees)
 // This reverses the process done by serialize_tree_to_bits
+// The input is untrusted: trees deeper than MAX_TREE_DEPTH are rejected as LimitExceeded and
+// repeated symbols as CorruptTree, so at most 256 leaves and 511 nodes are ever built
 pub fn deserialize_tree<R: std::io::Read>(
     bit_stream: &mut InputBitStream<R>,
-) -> std::io::Result<HuffmanNode> {
+) -> HuffmanResult<HuffmanNode> {
+    deserialize_node(bit_stream, 0, &mut [false; SYMBOL_COUNT])
+}
+
+// Reads the node found `depth` levels below the root
+fn deserialize_node<R: std::io::Read>(
+    bit_stream: &mut InputBitStream<R>,
+    depth: u32,
+    seen: &mut SeenSymbols,
+) -> HuffmanResult<HuffmanNode> {
     let node_type_bit = bit_stream.read_bit()?;
 
     match node_type_bit {
-        LEAF_NODE_BIT => deserialize_leaf_node(bit_stream),
-        INTERNAL_NODE_BIT => deserialize_internal_node(bit_stream),
-        _ => Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Invalid node type bit: {node_type_bit}"),
-        )),
+        LEAF_NODE_BIT => deserialize_leaf_node(bit_stream, seen),
+        INTERNAL_NODE_BIT if depth >= MAX_TREE_DEPTH => Err(HuffmanError::LimitExceeded(format!(
+            "Tree is deeper than {MAX_TREE_DEPTH} levels"
+        ))),
+        INTERNAL_NODE_BIT => deserialize_internal_node(bit_stream, depth, seen),
+        _ => Err(HuffmanError::CorruptTree(format!(
+            "Invalid node type bit: {node_type_bit}"
+        ))),
     }
 }
 
 // Reconstructs a leaf node from the bit stream
 // Reads the next 8 bits to get the symbol value
 // Creates a new leaf node with that symbol (frequency set to 1 since it's not needed for decompression)
+// A symbol that already has a leaf would make two codes decode to the same byte
 fn deserialize_leaf_node<R: std::io::Read>(
     bit_stream: &mut InputBitStream<R>,
-) -> std::io::Result<HuffmanNode> {
+    seen: &mut SeenSymbols,
+) -> HuffmanResult<HuffmanNode> {
     let symbol = read_symbol_from_bits(bit_stream)?;
+    if std::mem::replace(&mut seen[symbol as usize], true) {
+        return Err(HuffmanError::CorruptTree(format!(
+            "Symbol {symbol} appears more than once"
+        )));
+    }
     Ok(HuffmanNode::new_leaf(symbol, 1))
 }
 
@@ -41,21 +70,21 @@ fn deserialize_leaf_node<R: std::io::Read>(
 // Combines both children into a new internal node
 fn deserialize_internal_node<R: std::io::Read>(
     bit_stream: &mut InputBitStream<R>,
-) -> std::io::Result<HuffmanNode> {
-    deserialize_tree(bit_stream).and_then(|left_child| {
-        deserialize_tree(bit_stream)
+    depth: u32,
+    seen: &mut SeenSymbols,
+) -> HuffmanResult<HuffmanNode> {
+    deserialize_node(bit_stream, depth + 1, seen).and_then(|left_child| {
+        deserialize_node(bit_stream, depth + 1, seen)
             .map(|right_child| HuffmanNode::new_internal(left_child, right_child))
     })
 }
 
 // Reads exactly 8 bits from the stream and combines them into a byte
 // Reads bits from most significant to least significant position
-// Uses try_fold to accumulate bits: starts with 0, shifts left and adds each new bit
 // Example: reading bits 1,0,1,1,0,0,1,0 produces byte value 178
 fn read_symbol_from_bits<R: std::io::Read>(
     bit_stream: &mut InputBitStream<R>,
-) -> std::io::Result<u8> {
-    (0..BITS_PER_BYTE).try_fold(0u8, |acc, _| {
-        bit_stream.read_bit().map(|bit| (acc << 1) | bit)
-    })
+) -> HuffmanResult<u8> {
+    let symbol = bit_stream.read_bits(BITS_PER_BYTE as u32)?;
+    Ok(symbol as u8)
 }
@@ -17,17 +17,14 @@ pub fn serialize_tree_to_bits<W: Write>(
 }
 
 // Serializes a leaf node: writes marker bit '1' followed by the symbol's 8 bits
-// Uses an iterator chain to write the marker bit plus all symbol bits in sequence
-// The symbol bits are written from most significant to least significant
+// The marker and symbol go out as one 9-bit value, symbol bits most significant first
 fn serialize_leaf_to_bits<W: Write>(
     tree: &HuffmanNode,
     bit_stream: &mut OutputBitStream<W>,
 ) -> std::io::Result<()> {
     let symbol = tree.symbol().expect("Leaf node must have a symbol");
 
-    std::iter::once(1)
-        .chain((0..8).rev().map(|i| (symbol >> i) & 1))
-        .try_for_each(|bit| bit_stream.write_bit(bit))
+    bit_stream.write_bits((1 << 8) | symbol as u64, 9)
 }
 
 // Serializes an internal node: writes marker bit '0' followed by both children
@@ -0,0 +1,73 @@
+// Helpers shared by the unit and property tests
+// Each test binary uses only some of them
+#![allow(dead_code)]
+
+use std::io::Cursor;
+use tdd_huffman::{
+    encode_block_with, read_block_header, read_file_header, ByteFrequencyMap, CodeLengths,
+    CompressionOptions, EntropyCoder, HuffmanNode, InputBitStream,
+};
+
+// Frequencies following the Fibonacci sequence, the worst case for Huffman tree depth:
+// `count` symbols give a tree `count - 1` levels deep
+pub fn fibonacci_frequencies(count: usize) -> ByteFrequencyMap {
+    let mut frequencies = vec![1usize, 1];
+    while frequencies.len() < count {
+        let next = frequencies[frequencies.len() - 1] + frequencies[frequencies.len() - 2];
+        frequencies.push(next);
+    }
+    frequencies
+        .into_iter()
+        .enumerate()
+        .map(|(symbol, frequency)| (symbol as u8, frequency))
+        .collect()
+}
+
+// Builds a tree whose deepest leaves sit `depth` levels below the root:
+// every internal node has a leaf on the left and the rest of the chain on the right
+pub fn chain_tree(depth: u8) -> HuffmanNode {
+    (0..depth)
+        .rev()
+        .fold(HuffmanNode::new_leaf(depth, 1), |chain, symbol| {
+            HuffmanNode::new_internal(HuffmanNode::new_leaf(symbol, 1), chain)
+        })
+}
+
+// Walks the block headers of a compressed stream and returns each block's original length
+pub fn block_lengths(compressed_data: &[u8]) -> Vec<usize> {
+    let mut reader = compressed_data;
+    read_file_header(&mut reader).unwrap();
+    let mut lengths = Vec::new();
+    loop {
+        let block_header = read_block_header(&mut reader).unwrap();
+        if block_header.is_end() {
+            return lengths;
+        }
+        lengths.push(block_header.original_length as usize);
+        reader = &reader[block_header.payload_length as usize..];
+    }
+}
+
+// Total number of bits needed to encode every symbol occurrence with these lengths
+pub fn encoded_bits(lengths: &CodeLengths, frequency_map: &ByteFrequencyMap) -> usize {
+    frequency_map
+        .iter()
+        .map(|(&symbol, &frequency)| lengths[symbol as usize] as usize * frequency)
+        .sum()
+}
+
+// Encodes a block with coder C and the default options: its model, then its symbols
+pub fn encoded_with<C: EntropyCoder>(block: &[u8]) -> Vec<u8> {
+    encode_block_with::<C>(block, &CompressionOptions::default()).unwrap()
+}
+
+// Reads the model at the start of a payload and decodes `count` symbols after it with coder C
+pub fn decoded_with<C: EntropyCoder>(payload: &[u8], count: usize) -> Vec<u8> {
+    let mut bit_stream = InputBitStream::new(Cursor::new(payload));
+    let mut coder = C::deserialize_model(&mut bit_stream).unwrap();
+    let mut decoded = Vec::new();
+    coder
+        .decode_symbols(&mut bit_stream, count, &mut decoded)
+        .unwrap();
+    decoded
+}
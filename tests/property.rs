@@ -1,7 +1,25 @@
+#[path = "property/bit_stream_property_tests.rs"]
+mod bit_stream_property_tests;
+#[path = "property/canonical_codes_property_tests.rs"]
+mod canonical_codes_property_tests;
 #[path = "property/code_extraction_property_tests.rs"]
 mod code_extraction_property_tests;
+#[path = "common/mod.rs"]
+mod common;
 #[path = "property/compression_decompression_round_trip_tests.rs"]
 mod compression_decompression_round_trip_tests;
+#[path = "property/context_huffman_round_trip_tests.rs"]
+mod context_huffman_round_trip_tests;
+#[path = "property/decoding_table_property_tests.rs"]
+mod decoding_table_property_tests;
+#[path = "property/length_limited_codes_property_tests.rs"]
+mod length_limited_codes_property_tests;
+#[path = "property/malformed_input_property_tests.rs"]
+mod malformed_input_property_tests;
+#[path = "property/range_coder_round_trip_tests.rs"]
+mod range_coder_round_trip_tests;
+#[path = "property/tans_coder_round_trip_tests.rs"]
+mod tans_coder_round_trip_tests;
 #[path = "property/tree_construction_property_tests.rs"]
 mod tree_construction_property_tests;
 #[path = "property/tree_serialization_property_tests.rs"]
@@ -0,0 +1,48 @@
+use proptest::prelude::*;
+use tdd_huffman::*;
+
+proptest! {
+    #[test]
+    fn values_written_with_write_bits_read_back_with_read_bits(
+        fields in prop::collection::vec((any::<u64>(), 0u32..=64), 0..200),
+    ) {
+        let mut output = Vec::new();
+        let mut bit_stream = OutputBitStream::new(&mut output);
+        for &(value, count) in &fields {
+            bit_stream.write_bits(value, count).unwrap();
+        }
+        bit_stream.flush().unwrap();
+
+        let total_bits: u32 = fields.iter().map(|&(_, count)| count).sum();
+        prop_assert_eq!(output.len(), total_bits.div_ceil(8) as usize);
+
+        let mut bit_stream = InputBitStream::new(output.as_slice());
+        for &(value, count) in &fields {
+            let expected = if count == 0 { 0 } else { value & (u64::MAX >> (64 - count)) };
+            prop_assert_eq!(bit_stream.read_bits(count).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn write_bits_matches_writing_bit_by_bit(
+        fields in prop::collection::vec((any::<u64>(), 1u32..=64), 0..50),
+    ) {
+        let mut by_value = Vec::new();
+        let mut bit_stream = OutputBitStream::new(&mut by_value);
+        for &(value, count) in &fields {
+            bit_stream.write_bits(value, count).unwrap();
+        }
+        bit_stream.flush().unwrap();
+
+        let mut by_bit = Vec::new();
+        let mut bit_stream = OutputBitStream::new(&mut by_bit);
+        for &(value, count) in &fields {
+            for shift in (0..count).rev() {
+                bit_stream.write_bit(((value >> shift) & 1) as u8).unwrap();
+            }
+        }
+        bit_stream.flush().unwrap();
+
+        prop_assert_eq!(by_value, by_bit);
+    }
+}
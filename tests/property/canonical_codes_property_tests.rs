@@ -0,0 +1,72 @@
+use proptest::prelude::*;
+use tdd_huffman::*;
+
+// Builds a frequency map from arbitrary (symbol, frequency) pairs, keeping the last of duplicates
+fn frequency_map_from(pairs: Vec<(u8, usize)>) -> ByteFrequencyMap {
+    pairs.into_iter().collect()
+}
+
+proptest! {
+    #[test]
+    fn code_lengths_without_a_tree_match_the_tree(
+        pairs in prop::collection::vec((any::<u8>(), 1usize..1000), 1..300),
+    ) {
+        let frequency_map = frequency_map_from(pairs);
+        let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+
+        prop_assert_eq!(huffman_code_lengths(&frequency_map), Some(code_lengths_from_tree(&tree)));
+    }
+
+    #[test]
+    fn canonical_codes_keep_the_tree_code_lengths(
+        pairs in prop::collection::vec((any::<u8>(), 1usize..1000), 2..40),
+    ) {
+        let frequency_map = frequency_map_from(pairs);
+        prop_assume!(frequency_map.len() >= 2);
+        let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+
+        let tree_codes = extract_huffman_codes(&tree).expect("Tree is at most 64 levels deep");
+        let canonical_codes = assign_canonical_codes(&code_lengths_from_tree(&tree));
+
+        prop_assert_eq!(tree_codes.len(), canonical_codes.len());
+        for (symbol, code) in tree_codes.iter() {
+            prop_assert_eq!(Some(code.len), canonical_codes.get(symbol).map(|code| code.len));
+        }
+    }
+
+    #[test]
+    fn tree_rebuilt_from_lengths_decodes_the_canonical_codes(
+        pairs in prop::collection::vec((any::<u8>(), 1usize..1000), 1..40),
+    ) {
+        let frequency_map = frequency_map_from(pairs);
+        let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+        let lengths = code_lengths_from_tree(&tree);
+
+        let rebuilt = build_tree_from_code_lengths(&lengths).expect("Lengths come from a tree");
+
+        if frequency_map.len() == 1 {
+            prop_assert!(rebuilt.is_leaf());
+        } else {
+            let rebuilt_codes =
+                extract_huffman_codes(&rebuilt).expect("Tree is at most 64 levels deep");
+            prop_assert_eq!(rebuilt_codes, assign_canonical_codes(&lengths));
+        }
+    }
+
+    #[test]
+    fn code_lengths_survive_serialization(
+        pairs in prop::collection::vec((any::<u8>(), 1usize..1000), 1..256),
+    ) {
+        let frequency_map = frequency_map_from(pairs);
+        let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+        let lengths = code_lengths_from_tree(&tree);
+
+        let mut output = Vec::new();
+        let mut bit_stream = OutputBitStream::new(&mut output);
+        serialize_code_lengths(&lengths, &mut bit_stream).unwrap();
+        bit_stream.flush().unwrap();
+
+        let mut bit_stream = InputBitStream::new(output.as_slice());
+        prop_assert_eq!(deserialize_code_lengths(&mut bit_stream).unwrap(), lengths);
+    }
+}
@@ -47,8 +47,10 @@ proptest! {
         let (frequency_map, _) = count_frequencies(std::io::Cursor::new(&input_data)).unwrap();
         prop_assume!(frequency_map.len() >= 2); // Ensure unique symbols
 
-        let tree = build_huffman_tree(&frequency_map);
-        let codes = extract_huffman_codes(&tree);
+        let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+        let codes = extract_huffman_codes(&tree)
+            .expect("Tree is at most 64 levels deep")
+            .to_string_map();
 
         // Validate all properties
         prop_assert!(validates_prefix_free_property(&codes));
@@ -72,12 +74,14 @@ proptest! {
         let (frequency_map, _) = count_frequencies(std::io::Cursor::new(&input_data)).unwrap();
         prop_assume!(frequency_map.len() >= 2); // Ensure unique symbols
 
-        let tree = build_huffman_tree(&frequency_map);
-        let codes = extract_huffman_codes(&tree);
+        let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+        let codes = extract_huffman_codes(&tree)
+            .expect("Tree is at most 64 levels deep")
+            .to_string_map();
 
         // Sort symbols by frequency (descending) for comparison
         let mut sorted_pairs: Vec<(u8, usize)> = frequency_map.into_iter().collect();
-        sorted_pairs.sort_by(|a, b| b.1.cmp(&a.1));
+        sorted_pairs.sort_by_key(|pair| std::cmp::Reverse(pair.1));
 
         // Verify frequency-based code length property
         for i in 0..sorted_pairs.len() {
@@ -0,0 +1,98 @@
+use proptest::prelude::*;
+use std::io::Cursor;
+use tdd_huffman::{
+    compress_with_options, count_context_frequencies, decompress_parallel, encode_block_with,
+    read_range, CompressionOptions, ContextHuffmanCoder, DecompressionOptions, EntropyCoder,
+    InputBitStream, OutputBitStream, METHOD_CONTEXT_HUFFMAN,
+};
+
+// Bytes drawn from a small alphabet, so that contexts repeat and some earn their own table
+fn context_heavy_bytes(max_length: usize) -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(prop_oneof![8 => 0u8..6, 2 => any::<u8>()], 0..max_length)
+}
+
+proptest! {
+    #[test]
+    fn every_context_in_the_block_has_a_table(input in context_heavy_bytes(2000)) {
+        let contexts = count_context_frequencies(&input);
+        let coder =
+            ContextHuffmanCoder::from_context_frequencies(&contexts, &CompressionOptions::default());
+
+        match coder {
+            Some(coder) => contexts.iter().zip(coder.context_tables()).for_each(|(counts, table)| {
+                assert!(counts.is_empty() || table.is_some());
+            }),
+            None => prop_assert!(input.is_empty()),
+        }
+    }
+
+    #[test]
+    fn context_map_survives_serialization(input in context_heavy_bytes(2000)) {
+        let contexts = count_context_frequencies(&input);
+        let coder =
+            ContextHuffmanCoder::from_context_frequencies(&contexts, &CompressionOptions::default());
+        prop_assume!(coder.is_some());
+        let coder = coder.unwrap();
+        let mut serialized = Vec::new();
+        let mut bit_stream = OutputBitStream::new(&mut serialized);
+        coder.serialize_model(&mut bit_stream).unwrap();
+        bit_stream.flush().unwrap();
+
+        let mut bit_stream = InputBitStream::new(serialized.as_slice());
+        let read_back = ContextHuffmanCoder::deserialize_model(&mut bit_stream).unwrap();
+
+        prop_assert_eq!(read_back.context_tables(), coder.context_tables());
+        prop_assert_eq!(read_back.tables().len(), coder.tables().len());
+    }
+
+    #[test]
+    fn previous_byte_carries_over_between_decode_calls(
+        input in context_heavy_bytes(2000),
+        split in 0usize..2000,
+    ) {
+        let payload =
+            encode_block_with::<ContextHuffmanCoder>(&input, &CompressionOptions::default())
+                .unwrap();
+        prop_assume!(!input.is_empty());
+        let split = split.min(input.len());
+        let mut bit_stream = InputBitStream::new(payload.as_slice());
+        let mut coder = ContextHuffmanCoder::deserialize_model(&mut bit_stream).unwrap();
+
+        // The second call has to pick its first table from the last byte of the first call
+        let mut decoded = Vec::new();
+        coder.decode_symbols(&mut bit_stream, split, &mut decoded).unwrap();
+        coder.decode_symbols(&mut bit_stream, input.len() - split, &mut decoded).unwrap();
+
+        prop_assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn context_coded_streams_decode_in_parallel_and_by_range(
+        input in context_heavy_bytes(3000),
+        block_size in 1usize..1000,
+        threads in 1usize..4,
+        offset in 0u64..3500,
+        length in 0u64..1500,
+    ) {
+        let options = CompressionOptions {
+            block_size,
+            threads,
+            block_index: true,
+            method: METHOD_CONTEXT_HUFFMAN,
+            ..Default::default()
+        };
+        let mut compressed_data = Vec::new();
+        compress_with_options(input.as_slice(), &mut compressed_data, &options).unwrap();
+
+        let mut parallel = Vec::new();
+        let decompression_options = DecompressionOptions { threads, ..Default::default() };
+        decompress_parallel(Cursor::new(&compressed_data), &mut parallel, &decompression_options)
+            .unwrap();
+        let range = read_range(Cursor::new(&compressed_data), offset, length).unwrap();
+
+        prop_assert_eq!(&parallel, &input);
+        let start = (offset as usize).min(input.len());
+        let end = ((offset + length) as usize).min(input.len());
+        prop_assert_eq!(range.as_slice(), &input[start..end]);
+    }
+}
@@ -0,0 +1,44 @@
+use proptest::prelude::*;
+use tdd_huffman::*;
+
+proptest! {
+    #[test]
+    fn table_decoder_and_tree_walk_agree(
+        input in prop::collection::vec(any::<u8>(), 1..3000),
+        skew in 1usize..40,
+    ) {
+        // Repeating the first bytes skews the frequencies, producing codes of many lengths
+        let mut data = input.clone();
+        data.extend(input.iter().take(skew).flat_map(|&byte| vec![byte; skew * skew]));
+        let (frequency_map, _) = count_frequencies(data.as_slice()).unwrap();
+        let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+        prop_assume!(!tree.is_leaf());
+
+        let codes = extract_huffman_codes(&tree).expect("Tree is at most 64 levels deep");
+        let mut encoded = Vec::new();
+        let mut bit_stream = OutputBitStream::new(&mut encoded);
+        for &byte in &data {
+            let code = codes.code(byte);
+            bit_stream.write_bits(code.bits, code.len as u32).unwrap();
+        }
+        bit_stream.flush().unwrap();
+
+        let mut by_table = Vec::new();
+        decode_compressed_data(
+            &tree,
+            &mut InputBitStream::new(encoded.as_slice()),
+            &mut by_table,
+            data.len(),
+        ).unwrap();
+        let mut by_tree_walk = Vec::new();
+        decode_compressed_data_by_tree_walk(
+            &tree,
+            &mut InputBitStream::new(encoded.as_slice()),
+            &mut by_tree_walk,
+            data.len(),
+        ).unwrap();
+
+        prop_assert_eq!(&by_table, &data);
+        prop_assert_eq!(&by_tree_walk, &data);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::common::encoded_bits;
+use proptest::prelude::*;
+use tdd_huffman::*;
+
+proptest! {
+    #[test]
+    fn limited_lengths_respect_the_limit_and_rebuild_a_tree(
+        pairs in prop::collection::vec((any::<u8>(), 1usize..100_000), 1..256),
+        max_code_length in 8u8..=20,
+    ) {
+        let frequency_map: ByteFrequencyMap = pairs.into_iter().collect();
+
+        let lengths = length_limited_code_lengths(&frequency_map, max_code_length)
+            .expect("At most 256 symbols always fit in 8 bits");
+
+        prop_assert!(lengths.iter().all(|&length| length <= max_code_length));
+        prop_assert_eq!(
+            lengths.iter().filter(|&&length| length > 0).count(),
+            frequency_map.len()
+        );
+        prop_assert!(build_tree_from_code_lengths(&lengths).is_ok());
+    }
+
+    #[test]
+    fn limited_codes_never_beat_unlimited_huffman_codes(
+        pairs in prop::collection::vec((any::<u8>(), 1usize..100_000), 2..256),
+        max_code_length in 8u8..=20,
+    ) {
+        let frequency_map: ByteFrequencyMap = pairs.into_iter().collect();
+        prop_assume!(frequency_map.len() >= 2);
+        let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+        let huffman_lengths = code_lengths_from_tree(&tree);
+
+        let lengths = length_limited_code_lengths(&frequency_map, max_code_length)
+            .expect("At most 256 symbols always fit in 8 bits");
+
+        let huffman_cost = encoded_bits(&huffman_lengths, &frequency_map);
+        let limited_cost = encoded_bits(&lengths, &frequency_map);
+        prop_assert!(limited_cost >= huffman_cost);
+        if huffman_lengths.iter().all(|&length| length <= max_code_length) {
+            prop_assert_eq!(limited_cost, huffman_cost);
+        }
+    }
+
+    #[test]
+    fn round_trips_with_limited_code_lengths(
+        input in prop::collection::vec(any::<u8>(), 0..2000),
+        max_code_length in 8u8..=16,
+    ) {
+        let options = CompressionOptions {
+            max_code_length: Some(max_code_length),
+            ..Default::default()
+        };
+        let mut compressed_data = Vec::new();
+        compress_with_options(input.as_slice(), &mut compressed_data, &options).unwrap();
+
+        let mut output = Vec::new();
+        decompress(compressed_data.as_slice(), &mut output).unwrap();
+        prop_assert_eq!(output, input);
+    }
+}
@@ -0,0 +1,150 @@
+use proptest::prelude::*;
+use std::io::{Cursor, Read};
+use tdd_huffman::{
+    compress, compress_with_options, decompress_parallel, decompress_with_options, read_range,
+    write_file_header, CompressionOptions, DecompressionOptions, FileHeader, HuffmanDecoder,
+};
+
+// Output limit for every test below: forged block lengths must be refused, not honoured
+const MAX_OUTPUT_SIZE: u64 = 1 << 20;
+
+// Decompresses untrusted bytes under the output limit
+fn decompress_untrusted(data: &[u8]) -> Result<Vec<u8>, tdd_huffman::HuffmanError> {
+    let options = DecompressionOptions {
+        max_output_size: Some(MAX_OUTPUT_SIZE),
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    decompress_with_options(Cursor::new(data), &mut output, &options).map(|_| output)
+}
+
+// A valid file header with any coding method followed by one block with arbitrary type,
+// lengths and payload, so the fuzzing gets past the magic number and exercises the block decoders
+fn forged_block_stream() -> impl Strategy<Value = Vec<u8>> {
+    (
+        0u8..8,
+        any::<u8>(),
+        0u8..5,
+        any::<u32>(),
+        any::<u32>(),
+        prop::collection::vec(any::<u8>(), 0..256),
+    )
+        .prop_map(
+            |(block_type, flags, method, original_length, payload_length, rest)| {
+                let mut data = Vec::new();
+                let header = FileHeader::new(flags & 3).with_method(method);
+                write_file_header(&mut data, &header).unwrap();
+                data.push(block_type);
+                data.extend_from_slice(&original_length.to_le_bytes());
+                data.extend_from_slice(&payload_length.to_le_bytes());
+                data.extend_from_slice(&rest);
+                data
+            },
+        )
+}
+
+fn compressed(input: &[u8]) -> Vec<u8> {
+    let mut compressed_data = Vec::new();
+    compress(Cursor::new(input), &mut compressed_data).unwrap();
+    compressed_data
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_bytes_never_panic(data in prop::collection::vec(any::<u8>(), 0..1024)) {
+        // Any outcome but a panic is acceptable; random bytes almost never form a stream
+        let _ = decompress_untrusted(&data);
+    }
+
+    #[test]
+    fn forged_blocks_fail_cleanly_within_the_output_limit(data in forged_block_stream()) {
+        match decompress_untrusted(&data) {
+            Ok(output) => prop_assert!(output.len() as u64 <= MAX_OUTPUT_SIZE),
+            Err(error) => prop_assert!(!error.to_string().is_empty()),
+        }
+    }
+
+    #[test]
+    fn forged_blocks_never_panic_the_streaming_decoder(data in forged_block_stream()) {
+        let options = DecompressionOptions {
+            max_output_size: Some(MAX_OUTPUT_SIZE),
+            ..Default::default()
+        };
+        let mut decoder = HuffmanDecoder::with_options(Cursor::new(data), options);
+        let _ = decoder.read_to_end(&mut Vec::new());
+    }
+
+    #[test]
+    fn forged_blocks_never_panic_the_parallel_decoder(data in forged_block_stream()) {
+        let options = DecompressionOptions {
+            max_output_size: Some(MAX_OUTPUT_SIZE),
+            threads: 2,
+        };
+        let _ = decompress_parallel(Cursor::new(data), &mut Vec::new(), &options);
+    }
+
+    #[test]
+    fn forged_blocks_never_panic_range_reads(
+        data in forged_block_stream(),
+        offset in 0u64..1 << 33,
+        length in 0u64..4096,
+    ) {
+        if let Ok(range) = read_range(Cursor::new(data), offset, length) {
+            prop_assert!(range.len() as u64 <= length);
+        }
+    }
+
+    #[test]
+    fn corrupted_indexed_streams_are_detected_or_decode_correctly_in_parallel(
+        input in prop::collection::vec(any::<u8>(), 1..2000),
+        position in any::<prop::sample::Index>(),
+        mask in 1u8..=255,
+    ) {
+        let compression_options = CompressionOptions {
+            block_size: 300,
+            block_index: true,
+            ..Default::default()
+        };
+        let mut compressed_data = Vec::new();
+        compress_with_options(input.as_slice(), &mut compressed_data, &compression_options)
+            .unwrap();
+        let position = position.index(compressed_data.len());
+        compressed_data[position] ^= mask;
+
+        let options = DecompressionOptions {
+            max_output_size: Some(MAX_OUTPUT_SIZE),
+            threads: 3,
+        };
+        let mut output = Vec::new();
+        if decompress_parallel(Cursor::new(&compressed_data), &mut output, &options).is_ok() {
+            prop_assert_eq!(output, input);
+        }
+    }
+
+    #[test]
+    fn corrupted_streams_are_detected_or_decode_correctly(
+        input in prop::collection::vec(any::<u8>(), 1..2000),
+        position in any::<prop::sample::Index>(),
+        mask in 1u8..=255,
+    ) {
+        let mut compressed_data = compressed(&input);
+        let position = position.index(compressed_data.len());
+        compressed_data[position] ^= mask;
+
+        // The trailer's CRC-32 catches changed data, so success means the original came back
+        if let Ok(output) = decompress_untrusted(&compressed_data) {
+            prop_assert_eq!(output, input);
+        }
+    }
+
+    #[test]
+    fn truncated_streams_are_always_rejected(
+        input in prop::collection::vec(any::<u8>(), 0..2000),
+        cut in any::<prop::sample::Index>(),
+    ) {
+        let compressed_data = compressed(&input);
+        let length = cut.index(compressed_data.len());
+
+        prop_assert!(decompress_untrusted(&compressed_data[..length]).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use proptest::prelude::*;
+use rstest::rstest;
+use std::io::Cursor;
+use tdd_huffman::{
+    compress_with_options, decompress, decompress_parallel, read_range, CompressionOptions,
+    DecompressionOptions, METHOD_RANGE,
+};
+
+// Compresses with the range coder in blocks of the given size
+fn range_compress(input: &[u8], block_size: usize) -> Vec<u8> {
+    let options = CompressionOptions {
+        block_size,
+        method: METHOD_RANGE,
+        ..Default::default()
+    };
+    let mut compressed_data = Vec::new();
+    compress_with_options(input, &mut compressed_data, &options)
+        .expect("Compression should succeed");
+    compressed_data
+}
+
+fn round_trip(input: &[u8], block_size: usize) -> Vec<u8> {
+    let compressed_data = range_compress(input, block_size);
+    let mut output = Vec::new();
+    decompress(Cursor::new(compressed_data), &mut output).expect("Decompression should succeed");
+    output
+}
+
+#[rstest]
+#[case::single_char_a("a")]
+#[case::single_char_repeated_long("aaaa")]
+#[case::two_unique_chars("ab")]
+#[case::six_unique_chars("abcdef")]
+#[case::classic_abracadabra("abracadabra")]
+#[case::quick_brown_fox("the quick brown fox jumped over the lazy dog")]
+#[case::empty_string("")]
+#[case::mixed_whitespace(" \t\n")]
+#[case::numbers_and_punctuation("Hello, World! 123 $%^&*()")]
+#[case::unicode_basic("café naïve résumé")]
+fn test_range_coder_round_trip(#[case] input: &str) {
+    let output = round_trip(input.as_bytes(), 1 << 20);
+
+    assert_eq!(
+        input.as_bytes(),
+        output.as_slice(),
+        "Round-trip failed: input != decompressed output"
+    );
+}
+
+proptest! {
+    #[test]
+    fn range_coder_round_trip_property(
+        input in "[a-zA-Z0-9 .,!?]{1,100}"
+    ) {
+        prop_assert_eq!(round_trip(input.as_bytes(), 1 << 20), input.as_bytes());
+    }
+
+    #[test]
+    fn range_coder_round_trip_repeated_chars(
+        ch in prop::char::range('a', 'z'),
+        count in 1usize..=50
+    ) {
+        let input = ch.to_string().repeat(count);
+
+        prop_assert_eq!(round_trip(input.as_bytes(), 1 << 20), input.as_bytes());
+    }
+
+    #[test]
+    fn range_coder_round_trip_arbitrary_bytes(
+        input in prop::collection::vec(any::<u8>(), 0..5000),
+        block_size in 1usize..2000,
+    ) {
+        prop_assert_eq!(round_trip(&input, block_size), input);
+    }
+
+    #[test]
+    fn range_coder_round_trip_skewed_bytes(
+        input in prop::collection::vec(prop_oneof![98 => Just(0u8), 2 => any::<u8>()], 0..20_000),
+    ) {
+        prop_assert_eq!(round_trip(&input, 4096), input);
+    }
+
+    #[test]
+    fn range_coded_streams_decode_in_parallel_and_by_range(
+        input in prop::collection::vec(any::<u8>(), 0..5000),
+        block_size in 1usize..2000,
+        threads in 1usize..5,
+        offset in 0u64..6000,
+        length in 0u64..3000,
+    ) {
+        let options = CompressionOptions {
+            block_size,
+            threads,
+            block_index: true,
+            method: METHOD_RANGE,
+            ..Default::default()
+        };
+        let mut compressed_data = Vec::new();
+        compress_with_options(input.as_slice(), &mut compressed_data, &options).unwrap();
+
+        let mut parallel = Vec::new();
+        let decompression_options = DecompressionOptions { threads, ..Default::default() };
+        decompress_parallel(Cursor::new(&compressed_data), &mut parallel, &decompression_options)
+            .unwrap();
+        let range = read_range(Cursor::new(&compressed_data), offset, length).unwrap();
+
+        prop_assert_eq!(&parallel, &input);
+        let start = (offset as usize).min(input.len());
+        let end = ((offset + length) as usize).min(input.len());
+        prop_assert_eq!(range.as_slice(), &input[start..end]);
+    }
+}
@@ -0,0 +1,82 @@
+use proptest::prelude::*;
+use std::io::Cursor;
+use tdd_huffman::tans_coder::{spread_symbols, TABLE_LOG};
+use tdd_huffman::{
+    encode_block_with, normalize_frequencies, ByteFrequencyMap, CompressionOptions, EntropyCoder,
+    InputBitStream, ScaledFrequencies, TansCoder,
+};
+
+const TABLE_SIZE: usize = 1 << TABLE_LOG;
+
+// Normalized frequencies for arbitrary counts of up to 256 symbols
+fn normalized_frequencies() -> impl Strategy<Value = ScaledFrequencies> {
+    prop::collection::vec((any::<u8>(), 1usize..100_000), 1..256).prop_map(|pairs| {
+        let frequency_map: ByteFrequencyMap = pairs.into_iter().collect();
+        normalize_frequencies(&frequency_map, TABLE_LOG).expect("Counts are not empty")
+    })
+}
+
+proptest! {
+    #[test]
+    fn spread_gives_each_symbol_as_many_states_as_its_frequency(
+        frequencies in normalized_frequencies(),
+    ) {
+        let spread = spread_symbols(&frequencies);
+
+        prop_assert_eq!(spread.len(), TABLE_SIZE);
+        let mut states = [0u32; 256];
+        spread.iter().for_each(|&symbol| states[symbol as usize] += 1);
+        prop_assert_eq!(states, frequencies);
+    }
+
+    #[test]
+    fn every_decoder_state_leads_back_into_the_table(frequencies in normalized_frequencies()) {
+        let coder = TansCoder::new(frequencies);
+
+        for state in 0..TABLE_SIZE {
+            let (symbol, bits, base) = coder.decode_entry(state);
+            prop_assert!(frequencies[symbol as usize] > 0);
+            prop_assert!(bits <= TABLE_LOG);
+            prop_assert!(base + (1 << bits) <= TABLE_SIZE);
+        }
+    }
+
+    #[test]
+    fn decoding_undoes_each_encoder_step(
+        frequencies in normalized_frequencies(),
+        state in TABLE_SIZE..2 * TABLE_SIZE,
+        pick in any::<prop::sample::Index>(),
+    ) {
+        let coder = TansCoder::new(frequencies);
+        let present: Vec<u8> = (0..=255u8).filter(|&symbol| frequencies[symbol as usize] > 0).collect();
+        let symbol = present[pick.index(present.len())];
+
+        let (next, low_bits, bits) = coder.encode_step(state, symbol);
+
+        prop_assert!((TABLE_SIZE..2 * TABLE_SIZE).contains(&next));
+        let (decoded, decoded_bits, base) = coder.decode_entry(next - TABLE_SIZE);
+        prop_assert_eq!(decoded, symbol);
+        prop_assert_eq!(decoded_bits, bits);
+        prop_assert_eq!(base + low_bits as usize, state - TABLE_SIZE);
+    }
+
+    #[test]
+    fn dominant_symbols_round_trip_without_reading_bits(
+        input in prop::collection::vec(prop_oneof![98 => Just(0u8), 2 => any::<u8>()], 0..5000),
+        split in 0usize..5000,
+    ) {
+        // A symbol over half the table steps between states without reading any bits
+        let payload = encode_block_with::<TansCoder>(&input, &CompressionOptions::default())
+            .unwrap();
+        prop_assume!(!input.is_empty());
+        let split = split.min(input.len());
+        let mut bit_stream = InputBitStream::new(Cursor::new(payload));
+        let mut coder = TansCoder::deserialize_model(&mut bit_stream).unwrap();
+
+        let mut decoded = Vec::new();
+        coder.decode_symbols(&mut bit_stream, split, &mut decoded).unwrap();
+        coder.decode_symbols(&mut bit_stream, input.len() - split, &mut decoded).unwrap();
+
+        prop_assert_eq!(decoded, input);
+    }
+}
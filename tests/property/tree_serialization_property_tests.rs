@@ -43,7 +43,7 @@ proptest! {
         // Use the "she sells seashells on the seashore" example
         let input = b"she sells seashells on the seashore";
         let (freq_map, _) = count_frequencies(std::io::Cursor::new(input)).unwrap();
-        let tree = build_huffman_tree(&freq_map);
+        let tree = build_huffman_tree(&freq_map).expect("Frequency map is not empty");
 
         // Serialize the tree to bits using a Vec<u8> buffer
         let mut serialized_bits = Vec::new();
@@ -1,15 +1,59 @@
+#[path = "unit/adaptive_huffman_tests.rs"]
+mod adaptive_huffman_tests;
+#[path = "unit/block_format_tests.rs"]
+mod block_format_tests;
+#[path = "unit/block_index_tests.rs"]
+mod block_index_tests;
+#[path = "unit/canonical_codes_tests.rs"]
+mod canonical_codes_tests;
+#[path = "unit/checksum_tests.rs"]
+mod checksum_tests;
 #[path = "unit/code_extraction_tests.rs"]
 mod code_extraction_tests;
+#[path = "unit/code_length_serialization_tests.rs"]
+mod code_length_serialization_tests;
+#[path = "unit/coding_method_tests.rs"]
+mod coding_method_tests;
+#[path = "common/mod.rs"]
+mod common;
+#[path = "unit/compression_tests.rs"]
+mod compression_tests;
+#[path = "unit/context_huffman_tests.rs"]
+mod context_huffman_tests;
+#[path = "unit/decoder_tests.rs"]
+mod decoder_tests;
+#[path = "unit/decoding_table_tests.rs"]
+mod decoding_table_tests;
 #[path = "unit/decompression_tests.rs"]
 mod decompression_tests;
+#[path = "unit/encoder_tests.rs"]
+mod encoder_tests;
+#[path = "unit/entropy_coder_tests.rs"]
+mod entropy_coder_tests;
+#[path = "unit/error_tests.rs"]
+mod error_tests;
+#[path = "unit/file_header_tests.rs"]
+mod file_header_tests;
 #[path = "unit/frequency_map_tests.rs"]
 mod frequency_map_tests;
+#[path = "unit/frequency_table_tests.rs"]
+mod frequency_table_tests;
+#[path = "unit/huffman_coder_tests.rs"]
+mod huffman_coder_tests;
 #[path = "unit/input_bit_stream_tests.rs"]
 mod input_bit_stream_tests;
+#[path = "unit/length_limited_codes_tests.rs"]
+mod length_limited_codes_tests;
 #[path = "unit/node_selection_tests.rs"]
 mod node_selection_tests;
 #[path = "unit/output_bit_stream_tests.rs"]
 mod output_bit_stream_tests;
+#[path = "unit/random_access_tests.rs"]
+mod random_access_tests;
+#[path = "unit/range_coder_tests.rs"]
+mod range_coder_tests;
+#[path = "unit/tans_coder_tests.rs"]
+mod tans_coder_tests;
 #[path = "unit/tree_construction_tests.rs"]
 mod tree_construction_tests;
 #[path = "unit/tree_deserialization_tests.rs"]
@@ -0,0 +1,82 @@
+use crate::common::encoded_with;
+use std::io::Cursor;
+use tdd_huffman::{
+    AdaptiveHuffmanModel, EntropyCoder, HuffmanError, HuffmanResult, InputBitStream,
+};
+
+// Decodes `count` symbols from a payload with a fresh model
+fn decode_adaptive(payload: &[u8], count: usize) -> HuffmanResult<Vec<u8>> {
+    let mut model = AdaptiveHuffmanModel::new();
+    let mut bit_stream = InputBitStream::new(Cursor::new(payload));
+    (0..count)
+        .map(|_| model.decode_symbol(&mut bit_stream))
+        .collect()
+}
+
+#[test]
+fn first_symbol_is_sent_as_its_literal_bits() {
+    let payload = encoded_with::<AdaptiveHuffmanModel>(b"a");
+
+    assert_eq!(payload, vec![b'a']);
+}
+
+#[test]
+fn repeated_symbol_costs_one_bit() {
+    // 'a' as 01100001, then its leaf is the root's right child: 1
+    let payload = encoded_with::<AdaptiveHuffmanModel>(b"aa");
+
+    assert_eq!(payload, vec![0x61, 0x80]);
+}
+
+#[test]
+fn new_symbol_follows_the_path_to_nyt() {
+    // 'a' as 01100001, then the path to NYT (0) and 'b' as 01100010
+    let payload = encoded_with::<AdaptiveHuffmanModel>(b"ab");
+
+    assert_eq!(payload, vec![0x61, 0x31, 0x00]);
+}
+
+#[test]
+fn round_trips_every_byte_value_with_shifting_frequencies() {
+    // Early bytes are rare, later ones common, so the tree is rebalanced many times
+    let input: Vec<u8> = (0..=255u8)
+        .flat_map(|byte| std::iter::repeat_n(byte, byte as usize / 16 + 1))
+        .chain((0..2000u32).map(|i| (i * i % 7) as u8))
+        .collect();
+    let payload = encoded_with::<AdaptiveHuffmanModel>(&input);
+
+    let decoded = decode_adaptive(&payload, input.len()).unwrap();
+
+    assert_eq!(decoded, input);
+}
+
+#[test]
+fn frequent_symbols_get_short_codes() {
+    let input: Vec<u8> = (0..1000)
+        .map(|i| if i % 10 == 0 { b'b' } else { b'a' })
+        .collect();
+
+    let payload = encoded_with::<AdaptiveHuffmanModel>(&input);
+
+    // One bit per byte once the model has learned which symbol is common
+    assert!(payload.len() < 200, "Payload of {} bytes", payload.len());
+}
+
+#[test]
+fn rejects_symbol_introduced_twice() {
+    // 'a' as 01100001, then the path to NYT (0) followed by 'a' again
+    let payload = [0x61, 0x30, 0x80];
+
+    let error = decode_adaptive(&payload, 2).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::CorruptBlock(_)));
+}
+
+#[test]
+fn reports_truncated_payload() {
+    let payload = encoded_with::<AdaptiveHuffmanModel>(b"truncated");
+
+    let error = decode_adaptive(&payload[..2], 9).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::TruncatedStream));
+}
@@ -0,0 +1,87 @@
+use std::io::Cursor;
+use tdd_huffman::block_format::{BLOCK_TYPE_END, BLOCK_TYPE_HUFFMAN};
+use tdd_huffman::{
+    read_block_header, read_stream_trailer, write_block_header, write_stream_trailer, BlockHeader,
+    StreamTrailer,
+};
+
+#[test]
+fn writes_block_type_and_little_endian_lengths() {
+    let header = BlockHeader {
+        block_type: BLOCK_TYPE_HUFFMAN,
+        original_length: 0x0102_0304,
+        payload_length: 0x0A0B_0C0D,
+    };
+    let mut output = Vec::new();
+
+    write_block_header(&mut output, &header).unwrap();
+
+    assert_eq!(
+        output,
+        vec![
+            BLOCK_TYPE_HUFFMAN,
+            0x04,
+            0x03,
+            0x02,
+            0x01,
+            0x0D,
+            0x0C,
+            0x0B,
+            0x0A
+        ]
+    );
+    assert_eq!(read_block_header(&mut Cursor::new(output)).unwrap(), header);
+}
+
+#[test]
+fn end_marker_is_a_single_byte() {
+    let mut output = Vec::new();
+
+    write_block_header(&mut output, &BlockHeader::end()).unwrap();
+
+    assert_eq!(output, vec![BLOCK_TYPE_END]);
+    assert!(read_block_header(&mut Cursor::new(output))
+        .unwrap()
+        .is_end());
+}
+
+#[test]
+fn rejects_unknown_block_type() {
+    let data = vec![0xEE, 0, 0, 0, 0, 0, 0, 0, 0];
+
+    let error = read_block_header(&mut Cursor::new(data)).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn trailer_preserves_lengths_beyond_four_gibibytes() {
+    let trailer = StreamTrailer {
+        original_length: u32::MAX as u64 + 1,
+        checksum: Some(0xDEAD_BEEF),
+    };
+    let mut output = Vec::new();
+    write_stream_trailer(&mut output, &trailer).unwrap();
+
+    let read_back = read_stream_trailer(&mut Cursor::new(output), true).unwrap();
+
+    assert_eq!(read_back, trailer);
+    assert_eq!(read_back.original_length, 1 << 32);
+}
+
+#[test]
+fn trailer_without_checksum_is_only_the_length() {
+    let trailer = StreamTrailer {
+        original_length: 7,
+        checksum: None,
+    };
+    let mut output = Vec::new();
+
+    write_stream_trailer(&mut output, &trailer).unwrap();
+
+    assert_eq!(output, vec![7, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        read_stream_trailer(&mut Cursor::new(output), false).unwrap(),
+        trailer
+    );
+}
@@ -0,0 +1,130 @@
+use std::io::Cursor;
+use tdd_huffman::block_format::BLOCK_HEADER_SIZE;
+use tdd_huffman::file_header::FILE_HEADER_SIZE;
+use tdd_huffman::{
+    compress_with_options, read_block_header, read_block_index, read_file_header,
+    write_block_index, BlockIndexEntry, CompressionOptions, HuffmanError,
+};
+
+fn compressed_with_index(input: &[u8], block_size: usize) -> Vec<u8> {
+    let options = CompressionOptions {
+        block_size,
+        block_index: true,
+        ..Default::default()
+    };
+    let mut compressed_data = Vec::new();
+    compress_with_options(input, &mut compressed_data, &options)
+        .expect("Compression should succeed");
+    compressed_data
+}
+
+#[test]
+fn writes_little_endian_entries_followed_by_count() {
+    let entries = [BlockIndexEntry {
+        offset: 0x0102,
+        original_length: 0x0A0B,
+    }];
+    let mut output = Vec::new();
+
+    write_block_index(&mut output, &entries).unwrap();
+
+    assert_eq!(
+        output,
+        vec![0x02, 0x01, 0, 0, 0, 0, 0, 0, 0x0B, 0x0A, 0, 0, 1, 0, 0, 0]
+    );
+}
+
+#[test]
+fn index_records_where_every_block_starts() {
+    let input: Vec<u8> = (0..250).map(|i| b"indexed"[i % 7]).collect();
+    let compressed_data = compressed_with_index(&input, 100);
+
+    let index = read_block_index(&mut Cursor::new(&compressed_data), 0).unwrap();
+
+    // Walk the blocks to find their real offsets
+    let mut reader = compressed_data.as_slice();
+    assert!(read_file_header(&mut reader).unwrap().has_block_index());
+    let mut offset = FILE_HEADER_SIZE;
+    let expected: Vec<BlockIndexEntry> = (0..3)
+        .map(|_| {
+            let block_header = read_block_header(&mut reader).unwrap();
+            reader = &reader[block_header.payload_length as usize..];
+            let entry = BlockIndexEntry {
+                offset,
+                original_length: block_header.original_length,
+            };
+            offset += block_header.encoded_length();
+            entry
+        })
+        .collect();
+    assert_eq!(index, expected);
+    assert_eq!(
+        index.iter().map(|e| e.original_length).collect::<Vec<_>>(),
+        vec![100, 100, 50]
+    );
+}
+
+#[test]
+fn empty_input_has_an_empty_index() {
+    let compressed_data = compressed_with_index(b"", 100);
+
+    let index = read_block_index(&mut Cursor::new(&compressed_data), 0).unwrap();
+
+    assert!(index.is_empty());
+}
+
+#[test]
+fn rejects_count_larger_than_the_stream() {
+    let mut compressed_data = compressed_with_index(b"abcabc", 100);
+    let count_position = compressed_data.len() - 4;
+    compressed_data[count_position..].copy_from_slice(&1000u32.to_le_bytes());
+
+    let error = read_block_index(&mut Cursor::new(&compressed_data), 0).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::CorruptIndex(_)));
+}
+
+#[test]
+fn rejects_offsets_that_do_not_start_after_the_file_header() {
+    let mut compressed_data = compressed_with_index(b"abcabc", 100);
+    // One entry: 8 offset bytes and 4 length bytes before the 4-byte count
+    let offset_position = compressed_data.len() - 16;
+    compressed_data[offset_position] += BLOCK_HEADER_SIZE as u8;
+
+    let error = read_block_index(&mut Cursor::new(&compressed_data), 0).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::CorruptIndex(_)));
+}
+
+#[test]
+fn measures_the_index_from_where_the_stream_starts() {
+    let input: Vec<u8> = (0..250).map(|i| b"indexed"[i % 7]).collect();
+    let compressed_data = compressed_with_index(&input, 100);
+    let expected = read_block_index(&mut Cursor::new(&compressed_data), 0).unwrap();
+    // Bytes before the stream, as when it is embedded in a larger file
+    let prefix_length = 37;
+    let mut embedded = vec![0xAA; prefix_length];
+    embedded.extend_from_slice(&compressed_data);
+
+    let index = read_block_index(&mut Cursor::new(&embedded), prefix_length as u64).unwrap();
+
+    assert_eq!(index, expected);
+}
+
+#[test]
+fn rejects_offsets_past_the_index_of_a_stream_after_other_data() {
+    let mut compressed_data = compressed_with_index(b"abcabcabc", 5);
+    // Point the last of the two entries at the index itself, which lies within the stream's
+    // length only when the bytes before the stream are counted too
+    let index_start = compressed_data.len() as u64 - 2 * 12 - 4;
+    let offset_position = compressed_data.len() - 16;
+    compressed_data[offset_position..offset_position + 8]
+        .copy_from_slice(&(index_start + 10).to_le_bytes());
+    let prefix_length = 100;
+    let mut embedded = vec![0; prefix_length];
+    embedded.extend_from_slice(&compressed_data);
+
+    let error = read_block_index(&mut Cursor::new(&embedded), prefix_length as u64).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::CorruptIndex(_)));
+}
@@ -0,0 +1,116 @@
+use tdd_huffman::{
+    assign_canonical_codes, build_huffman_tree, build_tree_from_code_lengths,
+    code_lengths_from_tree, extract_huffman_codes, huffman_code_lengths, ByteFrequencyMap,
+    CodeLengths, HuffmanNode,
+};
+
+fn lengths_of(pairs: &[(u8, u8)]) -> CodeLengths {
+    let mut lengths = [0u8; 256];
+    pairs
+        .iter()
+        .for_each(|&(symbol, length)| lengths[symbol as usize] = length);
+    lengths
+}
+
+#[test]
+fn assigns_codes_from_lengths_like_deflate() {
+    // The worked example from RFC 1951, section 3.2.2
+    let lengths = lengths_of(&[
+        (b'A', 3),
+        (b'B', 3),
+        (b'C', 3),
+        (b'D', 3),
+        (b'E', 3),
+        (b'F', 2),
+        (b'G', 4),
+        (b'H', 4),
+    ]);
+
+    let codes = assign_canonical_codes(&lengths);
+
+    assert_eq!(codes.get(b'F').unwrap().to_string(), "00");
+    assert_eq!(codes.get(b'A').unwrap().to_string(), "010");
+    assert_eq!(codes.get(b'B').unwrap().to_string(), "011");
+    assert_eq!(codes.get(b'C').unwrap().to_string(), "100");
+    assert_eq!(codes.get(b'D').unwrap().to_string(), "101");
+    assert_eq!(codes.get(b'E').unwrap().to_string(), "110");
+    assert_eq!(codes.get(b'G').unwrap().to_string(), "1110");
+    assert_eq!(codes.get(b'H').unwrap().to_string(), "1111");
+}
+
+#[test]
+fn code_lengths_match_tree_depths() {
+    let frequency_map: ByteFrequencyMap = [(b'a', 5), (b'b', 2), (b'c', 1), (b'd', 3)]
+        .into_iter()
+        .collect();
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+
+    let lengths = code_lengths_from_tree(&tree);
+
+    extract_huffman_codes(&tree)
+        .expect("Tree is at most 64 levels deep")
+        .iter()
+        .for_each(|(symbol, code)| assert_eq!(lengths[symbol as usize], code.len));
+    assert_eq!(lengths.iter().filter(|&&length| length > 0).count(), 4);
+}
+
+#[test]
+fn computes_code_lengths_without_building_a_tree() {
+    let frequency_map: ByteFrequencyMap = [(b'a', 5), (b'b', 2), (b'c', 1), (b'd', 3)]
+        .into_iter()
+        .collect();
+
+    let lengths = huffman_code_lengths(&frequency_map);
+
+    assert_eq!(
+        lengths,
+        Some(lengths_of(&[(b'a', 1), (b'b', 3), (b'c', 3), (b'd', 2)]))
+    );
+    assert_eq!(
+        huffman_code_lengths(&[(b'z', 9)].into_iter().collect()),
+        Some(lengths_of(&[(b'z', 1)]))
+    );
+    assert_eq!(huffman_code_lengths(&ByteFrequencyMap::new()), None);
+}
+
+#[test]
+fn single_leaf_tree_gets_a_one_bit_code() {
+    let tree = HuffmanNode::new_leaf(b'z', 9);
+
+    let lengths = code_lengths_from_tree(&tree);
+
+    assert_eq!(lengths, lengths_of(&[(b'z', 1)]));
+    let rebuilt = build_tree_from_code_lengths(&lengths).expect("Single symbol is valid");
+    assert_eq!(rebuilt.symbol(), Some(b'z'));
+}
+
+#[test]
+fn rebuilt_tree_yields_the_canonical_codes() {
+    let lengths = lengths_of(&[(b'x', 1), (b'y', 2), (b'z', 2)]);
+
+    let tree = build_tree_from_code_lengths(&lengths).expect("Lengths are complete");
+
+    let codes = extract_huffman_codes(&tree).expect("Tree is at most 64 levels deep");
+    assert_eq!(codes, assign_canonical_codes(&lengths));
+    assert_eq!(codes.get(b'x').unwrap().to_string(), "0");
+    assert_eq!(codes.get(b'y').unwrap().to_string(), "10");
+    assert_eq!(codes.get(b'z').unwrap().to_string(), "11");
+}
+
+#[test]
+fn rejects_over_subscribed_lengths() {
+    let lengths = lengths_of(&[(1, 1), (2, 1), (3, 1)]);
+
+    let error = build_tree_from_code_lengths(&lengths).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_incomplete_lengths() {
+    let lengths = lengths_of(&[(1, 1), (2, 2)]);
+
+    let error = build_tree_from_code_lengths(&lengths).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
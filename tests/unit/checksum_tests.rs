@@ -0,0 +1,22 @@
+use tdd_huffman::{crc32, Crc32};
+
+#[test]
+fn checksum_of_empty_input_is_zero() {
+    assert_eq!(crc32(&[]), 0);
+}
+
+#[test]
+fn checksum_matches_standard_check_value() {
+    // The CRC-32/ISO-HDLC check value used by zip, gzip and PNG
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+}
+
+#[test]
+fn incremental_updates_match_one_shot_checksum() {
+    let data = b"the quick brown fox jumped over the lazy dog";
+    let mut checksum = Crc32::new();
+
+    data.chunks(7).for_each(|chunk| checksum.update(chunk));
+
+    assert_eq!(checksum.value(), crc32(data));
+}
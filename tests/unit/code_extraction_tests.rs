@@ -1,4 +1,5 @@
-use tdd_huffman::{extract_huffman_codes, HuffmanNode};
+use crate::common::chain_tree;
+use tdd_huffman::{extract_huffman_codes, HuffmanCode, HuffmanCodeTable, HuffmanNode};
 
 #[test]
 fn single_node_generates_single_bit_code() {
@@ -9,14 +10,14 @@ fn single_node_generates_single_bit_code() {
 
     let single_node = HuffmanNode::new_leaf(65u8, 10usize); // 'A' appears 10 times
 
-    let codes = extract_huffman_codes(&single_node);
+    let codes = extract_huffman_codes(&single_node).expect("Tree is at most 64 levels deep");
 
     // Should have exactly one entry
     assert_eq!(codes.len(), 1);
 
     // Should map symbol 'A' (65) to a single-bit code
-    assert!(codes.contains_key(&65u8));
-    let code = codes.get(&65u8).unwrap();
+    assert!(codes.contains(65u8));
+    let code = codes.get(65u8).unwrap().to_string();
 
     // Code should be exactly 1 bit (either "0" or "1")
     assert_eq!(code.len(), 1);
@@ -40,17 +41,17 @@ fn two_nodes_generate_complementary_codes() {
     let right_leaf = HuffmanNode::new_leaf(66u8, 5usize); // 'B' appears 5 times
     let tree_root = HuffmanNode::new_internal(left_leaf, right_leaf);
 
-    let codes = extract_huffman_codes(&tree_root);
+    let codes = extract_huffman_codes(&tree_root).expect("Tree is at most 64 levels deep");
 
     // Should have exactly two entries
     assert_eq!(codes.len(), 2);
 
     // Both symbols should be present
-    assert!(codes.contains_key(&65u8)); // 'A'
-    assert!(codes.contains_key(&66u8)); // 'B'
+    assert!(codes.contains(65u8)); // 'A'
+    assert!(codes.contains(66u8)); // 'B'
 
-    let code_a = codes.get(&65u8).unwrap();
-    let code_b = codes.get(&66u8).unwrap();
+    let code_a = codes.get(65u8).unwrap().to_string();
+    let code_b = codes.get(66u8).unwrap().to_string();
 
     // Both codes should be exactly 1 bit
     assert_eq!(code_a.len(), 1);
@@ -64,3 +65,57 @@ fn two_nodes_generate_complementary_codes() {
         code_b
     );
 }
+
+#[test]
+fn codes_are_packed_into_bits_and_length() {
+    // Tree structure:
+    //        root
+    //       /    \
+    //    A(65)   node
+    //           /    \
+    //        B(66)   C(67)
+    // Expected codes: A=0, B=10, C=11
+    let tree_root = HuffmanNode::new_internal(
+        HuffmanNode::new_leaf(65u8, 5),
+        HuffmanNode::new_internal(
+            HuffmanNode::new_leaf(66u8, 2),
+            HuffmanNode::new_leaf(67u8, 2),
+        ),
+    );
+
+    let codes = extract_huffman_codes(&tree_root).expect("Tree is at most 64 levels deep");
+
+    assert_eq!(codes.get(65u8), Some(HuffmanCode::new(0b0, 1)));
+    assert_eq!(codes.get(66u8), Some(HuffmanCode::new(0b10, 2)));
+    assert_eq!(codes.get(67u8), Some(HuffmanCode::new(0b11, 2)));
+    assert_eq!(codes.get(68u8), None);
+}
+
+#[test]
+fn code_renders_as_bit_string_with_leading_zeros() {
+    assert_eq!(HuffmanCode::new(0b0011, 4).to_string(), "0011");
+    assert_eq!(HuffmanCode::new(0, 1).to_string(), "0");
+    assert_eq!(HuffmanCode::default().to_string(), "");
+}
+
+#[test]
+fn symbols_without_a_code_have_zero_length_entries() {
+    let codes: HuffmanCodeTable = [(7u8, HuffmanCode::new(0b1, 1))].into_iter().collect();
+
+    assert_eq!(codes.len(), 1);
+    assert!(!codes.contains(8u8));
+    assert_eq!(codes.code(8u8).len, 0);
+    assert_eq!(
+        codes.iter().collect::<Vec<_>>(),
+        vec![(7u8, HuffmanCode::new(0b1, 1))]
+    );
+    assert!(HuffmanCodeTable::new().is_empty());
+}
+
+#[test]
+fn trees_deeper_than_a_u64_code_have_no_code_table() {
+    let codes = extract_huffman_codes(&chain_tree(64)).expect("64-bit codes still fit");
+
+    assert_eq!(codes.get(64).map(|code| code.len), Some(64));
+    assert!(extract_huffman_codes(&chain_tree(65)).is_none());
+}
@@ -0,0 +1,89 @@
+use tdd_huffman::{
+    build_huffman_tree, code_lengths_from_tree, count_frequencies, deserialize_code_lengths,
+    serialize_code_lengths, serialize_tree, serialized_code_lengths_bits, CodeLengths,
+    InputBitStream, OutputBitStream,
+};
+
+fn serialize(lengths: &CodeLengths) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut output);
+    serialize_code_lengths(lengths, &mut bit_stream).unwrap();
+    bit_stream.flush().unwrap();
+    output
+}
+
+#[test]
+fn round_trips_sparse_code_lengths() {
+    let mut lengths = [0u8; 256];
+    lengths[0] = 2;
+    lengths[b'e' as usize] = 2;
+    lengths[b't' as usize] = 3;
+    lengths[b'z' as usize] = 3;
+    lengths[255] = 2;
+
+    let bytes = serialize(&lengths);
+    let mut bit_stream = InputBitStream::new(bytes.as_slice());
+
+    assert_eq!(deserialize_code_lengths(&mut bit_stream).unwrap(), lengths);
+}
+
+#[test]
+fn text_code_lengths_are_smaller_than_the_tree_shape() {
+    let text = b"the quick brown fox jumps over the lazy dog";
+    let (frequency_map, _) = count_frequencies(text.as_slice()).unwrap();
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+
+    let bytes = serialize(&code_lengths_from_tree(&tree));
+
+    assert!(bytes.len() * 8 < serialize_tree(&tree).len());
+}
+
+#[test]
+fn full_alphabet_costs_five_bits_per_symbol_for_short_codes() {
+    let lengths = [8u8; 256];
+
+    let bytes = serialize(&lengths);
+
+    // 4 width bits plus 256 literals of 1 + 4 bits, versus 256 * 10 - 1 for the tree shape
+    assert_eq!(bytes.len(), (4 + 256 * 5usize).div_ceil(8));
+}
+
+#[test]
+fn counts_the_bits_serialization_writes() {
+    let mut sparse = [0u8; 256];
+    sparse[0] = 1;
+    sparse[200] = 1;
+    let mut text = [0u8; 256];
+    (b'a'..=b'z').for_each(|byte| text[byte as usize] = 5 + byte % 3);
+
+    // Width 1: a literal at 0, a run of 199, a literal at 200 and a run of 55
+    assert_eq!(serialized_code_lengths_bits(&sparse), 4 + 2 + 9 + 2 + 9);
+    assert_eq!(serialized_code_lengths_bits(&[8u8; 256]), 4 + 256 * 5);
+    [sparse, text, [8u8; 256], [0u8; 256]]
+        .iter()
+        .for_each(|lengths| {
+            assert_eq!(
+                serialize(lengths).len(),
+                serialized_code_lengths_bits(lengths).div_ceil(8)
+            )
+        });
+}
+
+#[test]
+fn rejects_zero_run_past_the_last_symbol() {
+    // Width field 0 (1-bit lengths), a zero run of 255, then a zero run of 2
+    let mut bits = vec![0, 0, 0, 0];
+    bits.extend([0, 1, 1, 1, 1, 1, 1, 1, 0]);
+    bits.extend([0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    let mut output = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut output);
+    bits.into_iter()
+        .try_for_each(|bit| bit_stream.write_bit(bit))
+        .unwrap();
+    bit_stream.flush().unwrap();
+
+    let mut bit_stream = InputBitStream::new(output.as_slice());
+    let error = deserialize_code_lengths(&mut bit_stream).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
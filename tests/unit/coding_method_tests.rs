@@ -0,0 +1,62 @@
+use tdd_huffman::{
+    coding_method, coding_method_named, compress_with_options, decompress, read_block_header,
+    read_file_header, AdaptiveHuffmanModel, CompressionOptions, ContextHuffmanCoder, EntropyCoder,
+    HuffmanCoder, RangeCoder, TansCoder, CODING_METHODS,
+};
+
+#[test]
+fn each_method_carries_its_coders_ids() {
+    let coders = [
+        (HuffmanCoder::METHOD, HuffmanCoder::BLOCK_TYPE),
+        (
+            AdaptiveHuffmanModel::METHOD,
+            AdaptiveHuffmanModel::BLOCK_TYPE,
+        ),
+        (RangeCoder::METHOD, RangeCoder::BLOCK_TYPE),
+        (TansCoder::METHOD, TansCoder::BLOCK_TYPE),
+        (ContextHuffmanCoder::METHOD, ContextHuffmanCoder::BLOCK_TYPE),
+    ];
+
+    coders.iter().for_each(|&(method, block_type)| {
+        assert_eq!(
+            coding_method(method).map(|coding| coding.block_type),
+            Some(block_type)
+        );
+    });
+    assert_eq!(CODING_METHODS.len(), coders.len());
+}
+
+#[test]
+fn every_method_writes_its_own_blocks() {
+    let input = b"one table drives every coder, one table drives every coder".repeat(20);
+
+    CODING_METHODS.iter().for_each(|coding| {
+        let options = CompressionOptions {
+            method: coding.id,
+            ..Default::default()
+        };
+        let mut compressed_data = Vec::new();
+        compress_with_options(input.as_slice(), &mut compressed_data, &options)
+            .expect("Compression should succeed");
+
+        let mut reader = compressed_data.as_slice();
+        assert_eq!(read_file_header(&mut reader).unwrap().method, coding.id);
+        assert_eq!(
+            read_block_header(&mut reader).unwrap().block_type,
+            coding.block_type
+        );
+        let mut output = Vec::new();
+        decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+        assert_eq!(output, input, "{} round trip", coding.name);
+    });
+}
+
+#[test]
+fn looks_methods_up_by_name() {
+    assert_eq!(
+        coding_method_named("tans").map(|coding| coding.id),
+        Some(TansCoder::METHOD)
+    );
+    assert!(coding_method_named("lzma").is_none());
+    assert!(coding_method(0xFF).is_none());
+}
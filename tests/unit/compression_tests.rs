@@ -0,0 +1,203 @@
+use crate::common::block_lengths;
+use std::io::Read;
+use tdd_huffman::block_format::{BLOCK_TYPE_ADAPTIVE, BLOCK_TYPE_STORED, DEFAULT_BLOCK_SIZE};
+use tdd_huffman::{
+    compress_stream, compress_with_options, decompress, read_block_header, read_file_header,
+    CompressionOptions, METHOD_ADAPTIVE_HUFFMAN,
+};
+
+// Non-seekable reader that hands out at most a few bytes per call, like a slow pipe
+struct TricklingReader<'a> {
+    data: &'a [u8],
+}
+
+impl Read for TricklingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = buf.len().min(self.data.len()).min(3);
+        buf[..count].copy_from_slice(&self.data[..count]);
+        self.data = &self.data[count..];
+        Ok(count)
+    }
+}
+
+#[test]
+fn compresses_from_non_seekable_reader() {
+    let input = b"streamed through a pipe, a few bytes at a time";
+    let mut compressed_data = Vec::new();
+
+    compress_stream(TricklingReader { data: input }, &mut compressed_data)
+        .expect("Compression should succeed");
+
+    let mut output = Vec::new();
+    decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+    assert_eq!(output, input);
+}
+
+#[test]
+fn splits_long_input_into_several_self_contained_blocks() {
+    let input: Vec<u8> = (0..DEFAULT_BLOCK_SIZE * 2 + 100)
+        .map(|i| (i % 7) as u8)
+        .collect();
+    let mut compressed_data = Vec::new();
+
+    compress_stream(input.as_slice(), &mut compressed_data).expect("Compression should succeed");
+
+    assert_eq!(
+        block_lengths(&compressed_data),
+        vec![DEFAULT_BLOCK_SIZE, DEFAULT_BLOCK_SIZE, 100],
+        "Each full block should be cut at the block size"
+    );
+
+    let mut output = Vec::new();
+    decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+    assert_eq!(output, input);
+}
+
+#[test]
+fn cuts_blocks_at_the_configured_block_size() {
+    let input = vec![b'z'; 250];
+    let options = CompressionOptions {
+        block_size: 100,
+        ..Default::default()
+    };
+    let mut compressed_data = Vec::new();
+
+    compress_with_options(input.as_slice(), &mut compressed_data, &options)
+        .expect("Compression should succeed");
+
+    assert_eq!(block_lengths(&compressed_data), vec![100, 100, 50]);
+}
+
+#[test]
+fn per_block_trees_adapt_to_changing_statistics() {
+    // Two halves with disjoint alphabets: one tree for everything needs longer codes
+    // than a separate tree for each half
+    let first_half: Vec<u8> = (0..4096).map(|i| b"ab"[i % 2]).collect();
+    let second_half: Vec<u8> = (0..4096).map(|i| b"xy"[i % 2]).collect();
+    let input = [first_half, second_half].concat();
+
+    let compressed_size = |block_size| {
+        let mut compressed_data = Vec::new();
+        compress_with_options(
+            input.as_slice(),
+            &mut compressed_data,
+            &CompressionOptions {
+                block_size,
+                ..Default::default()
+            },
+        )
+        .expect("Compression should succeed");
+        compressed_data.len()
+    };
+
+    assert!(compressed_size(4096) < compressed_size(input.len()));
+}
+
+#[test]
+fn rejects_zero_block_size() {
+    let options = CompressionOptions {
+        block_size: 0,
+        ..Default::default()
+    };
+
+    let error = compress_with_options(b"abc".as_slice(), &mut Vec::new(), &options)
+        .expect_err("A zero block size cannot make progress");
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn parallel_compression_matches_sequential_output() {
+    let input: Vec<u8> = (0..50_000u32).map(|i| (i * i % 251) as u8).collect();
+    let compress_with_threads = |threads| {
+        let options = CompressionOptions {
+            block_size: 4096,
+            threads,
+            ..Default::default()
+        };
+        let mut compressed_data = Vec::new();
+        compress_with_options(input.as_slice(), &mut compressed_data, &options)
+            .expect("Compression should succeed");
+        compressed_data
+    };
+
+    let sequential = compress_with_threads(1);
+    let parallel = compress_with_threads(4);
+
+    assert_eq!(parallel, sequential);
+    assert_eq!(block_lengths(&parallel).len(), 13);
+}
+
+#[test]
+fn rejects_zero_threads() {
+    let options = CompressionOptions {
+        threads: 0,
+        ..Default::default()
+    };
+
+    let error = compress_with_options(b"abc".as_slice(), &mut Vec::new(), &options)
+        .expect_err("Compression needs at least one thread");
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn adaptive_method_is_recorded_in_the_file_header() {
+    let input = b"adaptive codes learn as they go, adaptive codes learn as they go".repeat(10);
+    let options = CompressionOptions {
+        method: METHOD_ADAPTIVE_HUFFMAN,
+        ..Default::default()
+    };
+    let mut compressed_data = Vec::new();
+
+    compress_with_options(input.as_slice(), &mut compressed_data, &options)
+        .expect("Compression should succeed");
+
+    let mut reader = compressed_data.as_slice();
+    assert_eq!(
+        read_file_header(&mut reader).unwrap().method,
+        METHOD_ADAPTIVE_HUFFMAN
+    );
+    let block_header = read_block_header(&mut reader).unwrap();
+    assert_eq!(block_header.block_type, BLOCK_TYPE_ADAPTIVE);
+
+    let mut output = Vec::new();
+    decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+    assert_eq!(output, input);
+}
+
+#[test]
+fn rejects_unknown_method() {
+    let options = CompressionOptions {
+        method: 0xFF,
+        ..Default::default()
+    };
+
+    let error = compress_with_options(b"abc".as_slice(), &mut Vec::new(), &options)
+        .expect_err("Compression needs a known method");
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn stores_incompressible_blocks_verbatim() {
+    // Every byte value exactly once: a Huffman tree over 256 equally likely symbols
+    // costs 8 bits per byte plus the tree itself
+    let input: Vec<u8> = (0..=255u8).map(|i| i.wrapping_mul(167)).collect();
+    let mut compressed_data = Vec::new();
+
+    compress_stream(input.as_slice(), &mut compressed_data).expect("Compression should succeed");
+
+    let mut reader = compressed_data.as_slice();
+    read_file_header(&mut reader).unwrap();
+    let block_header = read_block_header(&mut reader).unwrap();
+    assert_eq!(block_header.block_type, BLOCK_TYPE_STORED);
+    assert_eq!(&reader[..input.len()], input.as_slice());
+
+    // File header, block header, end marker and trailer are the only overhead
+    assert_eq!(compressed_data.len(), input.len() + 7 + 9 + 1 + 12);
+
+    let mut output = Vec::new();
+    decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+    assert_eq!(output, input);
+}
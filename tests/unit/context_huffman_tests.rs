@@ -0,0 +1,117 @@
+use crate::common::{decoded_with, encoded_with};
+use std::io::Cursor;
+use tdd_huffman::{
+    count_context_frequencies, count_frequencies, encode_block_with, CompressionOptions,
+    ContextHuffmanCoder, EntropyCoder, HuffmanCoder, HuffmanError, InputBitStream, OutputBitStream,
+};
+
+// Text where each letter is almost always followed by the same next letter
+fn predictable_text(length: usize) -> Vec<u8> {
+    b"the quick brown fox jumps over the lazy dog, "
+        .iter()
+        .copied()
+        .cycle()
+        .take(length)
+        .collect()
+}
+
+#[test]
+fn predictable_successors_beat_order_zero_codes() {
+    let input = predictable_text(100_000);
+
+    let context_payload = encoded_with::<ContextHuffmanCoder>(&input);
+    let huffman_payload =
+        encode_block_with::<HuffmanCoder>(&input, &CompressionOptions::default()).unwrap();
+
+    assert!(
+        context_payload.len() * 4 < huffman_payload.len(),
+        "Context coding took {} bytes, Huffman {}",
+        context_payload.len(),
+        huffman_payload.len()
+    );
+}
+
+#[test]
+fn frequent_contexts_get_their_own_tables() {
+    let input = predictable_text(100_000);
+    let contexts = count_context_frequencies(&input);
+
+    let coder =
+        ContextHuffmanCoder::from_context_frequencies(&contexts, &CompressionOptions::default())
+            .unwrap();
+
+    assert!(coder.context_tables()[b'q' as usize].is_some());
+    assert_ne!(
+        coder.context_tables()[b'q' as usize],
+        coder.context_tables()[b'o' as usize]
+    );
+}
+
+#[test]
+fn rare_contexts_share_one_table() {
+    // A short block cannot pay for a table per context
+    let input = b"abcdefghijklmnopqrstuvwxyz";
+    let contexts = count_context_frequencies(input);
+
+    let coder =
+        ContextHuffmanCoder::from_context_frequencies(&contexts, &CompressionOptions::default())
+            .unwrap();
+
+    assert_eq!(coder.tables().len(), 1);
+    assert!(coder.shared_table().is_some());
+    assert!(coder.context_tables().iter().all(|&table| table == Some(0)));
+    assert_eq!(
+        decoded_with::<ContextHuffmanCoder>(&encoded_with::<ContextHuffmanCoder>(input), 26),
+        input
+    );
+}
+
+#[test]
+fn order_zero_counts_build_a_single_shared_table() {
+    let (frequency_map, _) = count_frequencies(b"abracadabra".as_slice()).unwrap();
+
+    let coder =
+        ContextHuffmanCoder::from_frequencies(&frequency_map, &CompressionOptions::default())
+            .unwrap();
+
+    assert_eq!(coder.tables().len(), 1);
+    assert!(coder.shared_table().is_some());
+}
+
+#[test]
+fn rejects_contexts_out_of_order() {
+    let mut serialized = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut serialized);
+    bit_stream.write_bits(2, 9).unwrap();
+    bit_stream.write_bits(b'b' as u64, 8).unwrap();
+    bit_stream.write_bits(b'a' as u64, 8).unwrap();
+    bit_stream.flush().unwrap();
+
+    let error =
+        ContextHuffmanCoder::deserialize_model(&mut InputBitStream::new(Cursor::new(serialized)));
+
+    assert!(matches!(error, Err(HuffmanError::CorruptTree(_))));
+}
+
+#[test]
+fn rejects_a_context_without_a_table() {
+    // Only context 'a' has a table and there is no shared one, so the first byte of the
+    // block, which follows no byte at all, cannot be decoded
+    let (frequency_map, _) = count_frequencies(b"abc".as_slice()).unwrap();
+    let table =
+        HuffmanCoder::from_frequencies(&frequency_map, &CompressionOptions::default()).unwrap();
+    let mut serialized = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut serialized);
+    bit_stream.write_bits(1, 9).unwrap();
+    bit_stream.write_bits(b'a' as u64, 8).unwrap();
+    table.serialize_model(&mut bit_stream).unwrap();
+    bit_stream.write_bit(0).unwrap();
+    bit_stream.write_bits(0, 32).unwrap();
+    bit_stream.flush().unwrap();
+    let mut bit_stream = InputBitStream::new(Cursor::new(serialized));
+    let mut coder = ContextHuffmanCoder::deserialize_model(&mut bit_stream).unwrap();
+
+    let error = coder.decode_symbol(&mut bit_stream);
+
+    assert!(matches!(error, Err(HuffmanError::CorruptBlock(_))));
+}
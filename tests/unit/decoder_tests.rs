@@ -0,0 +1,110 @@
+use std::io::{BufRead, Cursor, Read};
+use tdd_huffman::{compress, compress_with_options, CompressionOptions, HuffmanDecoder};
+
+// Compresses the input with the given block size into an in-memory stream
+fn compressed(input: &[u8], block_size: usize) -> Vec<u8> {
+    let options = CompressionOptions {
+        block_size,
+        ..Default::default()
+    };
+    let mut compressed_data = Vec::new();
+    compress_with_options(input, &mut compressed_data, &options)
+        .expect("Compression should succeed");
+    compressed_data
+}
+
+#[test]
+fn reads_back_the_original_in_small_pieces() {
+    let input: Vec<u8> = (0..20_000).map(|i| b"small reads"[i % 11]).collect();
+    let compressed_data = compressed(&input, 3000);
+    let mut decoder = HuffmanDecoder::new(compressed_data.as_slice());
+
+    let mut output = Vec::new();
+    let mut piece = [0u8; 7];
+    loop {
+        let count = decoder.read(&mut piece).expect("Read should succeed");
+        if count == 0 {
+            break;
+        }
+        output.extend_from_slice(&piece[..count]);
+    }
+
+    assert_eq!(output, input);
+}
+
+#[test]
+fn supports_buffered_line_reading() {
+    let input = b"first line\nsecond line\nthird line\n";
+    let mut compressed_data = Vec::new();
+    compress(input.as_slice(), &mut compressed_data).unwrap();
+
+    let lines: Vec<String> = HuffmanDecoder::new(compressed_data.as_slice())
+        .lines()
+        .collect::<Result<_, _>>()
+        .expect("Lines should decode");
+
+    assert_eq!(lines, vec!["first line", "second line", "third line"]);
+}
+
+#[test]
+fn reads_compressed_input_only_as_far_as_needed() {
+    // Forty blocks of 1000 bytes; reading a few bytes must not pull in the whole stream
+    let input: Vec<u8> = (0..40_000).map(|i| (i % 13) as u8).collect();
+    let compressed_data = compressed(&input, 1000);
+    let mut decoder = HuffmanDecoder::new(Cursor::new(compressed_data.as_slice()));
+
+    let mut first_bytes = [0u8; 10];
+    decoder.read_exact(&mut first_bytes).unwrap();
+    let consumed = decoder.into_inner().position() as usize;
+
+    assert_eq!(first_bytes.as_slice(), &input[..10]);
+    assert!(consumed * 10 < compressed_data.len());
+}
+
+#[test]
+fn reports_corruption_when_reaching_the_end() {
+    let mut compressed_data = Vec::new();
+    compress(b"checked at the end".as_slice(), &mut compressed_data).unwrap();
+    let last = compressed_data.len() - 1;
+    compressed_data[last] ^= 0x01;
+
+    let mut output = Vec::new();
+    let error = HuffmanDecoder::new(compressed_data.as_slice())
+        .read_to_end(&mut output)
+        .expect_err("Checksum mismatch should be reported");
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("Checksum mismatch"));
+}
+
+#[test]
+fn keeps_reporting_corruption_after_a_failed_trailer() {
+    let mut compressed_data = Vec::new();
+    compress(b"checked at the end".as_slice(), &mut compressed_data).unwrap();
+    let last = compressed_data.len() - 1;
+    compressed_data[last] ^= 0x01;
+    let mut decoder = HuffmanDecoder::new(compressed_data.as_slice());
+
+    let mut output = Vec::new();
+    decoder
+        .read_to_end(&mut output)
+        .expect_err("Checksum mismatch should be reported");
+
+    // Reading again must not look like a clean end of the stream
+    assert!(decoder.read(&mut [0u8; 4]).is_err());
+    assert!(decoder.fill_buf().is_err());
+}
+
+#[test]
+fn keeps_returning_end_of_file_after_the_stream() {
+    let mut compressed_data = Vec::new();
+    compress(b"done".as_slice(), &mut compressed_data).unwrap();
+    let mut decoder = HuffmanDecoder::new(compressed_data.as_slice());
+
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).unwrap();
+
+    assert_eq!(output, b"done");
+    assert_eq!(decoder.read(&mut [0u8; 4]).unwrap(), 0);
+    assert!(decoder.fill_buf().unwrap().is_empty());
+}
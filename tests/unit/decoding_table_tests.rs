@@ -0,0 +1,93 @@
+use crate::common::fibonacci_frequencies;
+use tdd_huffman::{
+    build_huffman_tree, decode_compressed_data, decode_compressed_data_by_tree_walk,
+    extract_huffman_codes, DecodingTable, HuffmanNode, InputBitStream, OutputBitStream,
+};
+
+// Encodes the symbols with the tree's codes and pads the result to whole bytes
+fn encode_with_tree(tree: &HuffmanNode, symbols: &[u8]) -> Vec<u8> {
+    let codes = extract_huffman_codes(tree).expect("Tree is at most 64 levels deep");
+    let mut encoded = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut encoded);
+    symbols.iter().for_each(|&symbol| {
+        let code = codes.code(symbol);
+        bit_stream.write_bits(code.bits, code.len as u32).unwrap();
+    });
+    bit_stream.flush().unwrap();
+    encoded
+}
+
+#[test]
+fn decodes_short_codes_one_symbol_per_lookup() {
+    // A (left, code 0) and B (right, code 1)
+    let tree = HuffmanNode::new_internal(
+        HuffmanNode::new_leaf(b'A', 1),
+        HuffmanNode::new_leaf(b'B', 1),
+    );
+    let table = DecodingTable::new(&tree);
+    let encoded = [0b0110_0010u8];
+    let mut bit_stream = InputBitStream::new(encoded.as_slice());
+
+    let decoded: Vec<u8> = (0..8)
+        .map(|_| table.decode_symbol(&mut bit_stream).unwrap())
+        .collect();
+
+    assert_eq!(decoded, b"ABBAAABA");
+}
+
+#[test]
+fn falls_back_to_the_tree_for_codes_longer_than_the_table() {
+    // 20 Fibonacci frequencies give codes up to 19 bits long
+    let tree = build_huffman_tree(&fibonacci_frequencies(20)).expect("Frequency map is not empty");
+    let symbols: Vec<u8> = (0..20).chain((0..20).rev()).collect();
+    let encoded = encode_with_tree(&tree, &symbols);
+    let table = DecodingTable::new(&tree);
+    let mut bit_stream = InputBitStream::new(encoded.as_slice());
+
+    let decoded: Vec<u8> = symbols
+        .iter()
+        .map(|_| table.decode_symbol(&mut bit_stream).unwrap())
+        .collect();
+
+    assert_eq!(decoded, symbols);
+}
+
+#[test]
+fn table_decoder_matches_tree_walk() {
+    let tree = build_huffman_tree(&fibonacci_frequencies(16)).expect("Frequency map is not empty");
+    let symbols: Vec<u8> = (0..1000).map(|i| ((i * 7) % 16) as u8).collect();
+    let encoded = encode_with_tree(&tree, &symbols);
+
+    let mut by_table = Vec::new();
+    decode_compressed_data(
+        &tree,
+        &mut InputBitStream::new(encoded.as_slice()),
+        &mut by_table,
+        symbols.len(),
+    )
+    .unwrap();
+    let mut by_tree_walk = Vec::new();
+    decode_compressed_data_by_tree_walk(
+        &tree,
+        &mut InputBitStream::new(encoded.as_slice()),
+        &mut by_tree_walk,
+        symbols.len(),
+    )
+    .unwrap();
+
+    assert_eq!(by_table, symbols);
+    assert_eq!(by_tree_walk, symbols);
+}
+
+#[test]
+fn reports_truncated_input() {
+    // The last code needs more bits than the input holds
+    let tree = build_huffman_tree(&fibonacci_frequencies(12)).expect("Frequency map is not empty");
+    let encoded = encode_with_tree(&tree, &[0, 0]);
+    let table = DecodingTable::new(&tree);
+    let mut bit_stream = InputBitStream::new(&encoded[..1]);
+
+    let error = table.decode_symbol(&mut bit_stream).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
@@ -1,28 +1,66 @@
 use std::io::Cursor;
-use tdd_huffman::{decompress, serialize_tree_to_bits, HuffmanNode, OutputBitStream};
+use tdd_huffman::block_format::{BLOCK_TYPE_HUFFMAN, BLOCK_TYPE_STORED};
+use tdd_huffman::{
+    compress, compress_with_options, decompress, decompress_parallel, decompress_with_options,
+    read_block_header, serialize_tree_to_bits, write_block_header, write_file_header,
+    write_stream_trailer, BlockHeader, CompressionOptions, DecompressionOptions, FileHeader,
+    HuffmanError, HuffmanNode, OutputBitStream, StreamTrailer, METHOD_ADAPTIVE_HUFFMAN,
+};
+
+// Wraps already-encoded block payloads into a complete stream without a checksum
+// Each entry is (original length, payload)
+fn build_stream(blocks: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut compressed_data = Vec::new();
+    write_file_header(&mut compressed_data, &FileHeader::new(0)).expect("Header should be written");
+
+    for (original_length, payload) in blocks {
+        let block_header = BlockHeader {
+            block_type: BLOCK_TYPE_HUFFMAN,
+            original_length: *original_length,
+            payload_length: payload.len() as u32,
+        };
+        write_block_header(&mut compressed_data, &block_header).expect("Block should be written");
+        compressed_data.extend_from_slice(payload);
+    }
+
+    let original_length = blocks.iter().map(|(length, _)| *length as u64).sum();
+    write_block_header(&mut compressed_data, &BlockHeader::end()).expect("End should be written");
+    write_stream_trailer(
+        &mut compressed_data,
+        &StreamTrailer {
+            original_length,
+            checksum: None,
+        },
+    )
+    .expect("Trailer should be written");
+
+    compressed_data
+}
+
+// Reads the payload length of the first block of a compressed stream
+fn single_block_payload_length(compressed_data: &[u8]) -> usize {
+    let mut reader = &compressed_data[7..];
+    read_block_header(&mut reader)
+        .expect("Stream should hold a block")
+        .payload_length as usize
+}
 
 #[test]
 fn decompresses_ten_zeros_to_ten_as_with_single_node_tree() {
     // Arrange: Tree with single node for 'A'
     let tree = HuffmanNode::new_leaf(b'A', 10);
 
-    // Create properly formatted compressed data with header and serialized tree
-    let mut compressed_data = Vec::new();
-
-    // Write the original data length as a 4-byte little-endian header
-    let original_length = 10u32;
-    compressed_data.extend_from_slice(&original_length.to_le_bytes());
-
-    // Serialize the tree to the compressed data
-    let mut bit_stream = OutputBitStream::new(&mut compressed_data);
+    // Serialize the tree into the block payload
+    let mut payload = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut payload);
     serialize_tree_to_bits(&tree, &mut bit_stream).expect("Tree serialization should succeed");
 
     // For single-node tree, no additional bits are needed for the data itself
     // (the tree structure alone determines the output)
     bit_stream.flush().expect("Should flush successfully");
 
-    // Create input reader from compressed data
-    let cursor = Cursor::new(compressed_data);
+    // Create input reader from a stream holding that single block
+    let cursor = Cursor::new(build_stream(&[(10, payload)]));
 
     // Output stream to write decompressed data
     let mut output = Vec::new();
@@ -42,15 +80,9 @@ fn decompresses_bits_to_ab_sequence_with_two_node_tree() {
     let right_leaf = HuffmanNode::new_leaf(b'B', 1);
     let tree = HuffmanNode::new_internal(left_leaf, right_leaf);
 
-    // Create properly formatted compressed data with header and serialized tree
-    let mut compressed_data = Vec::new();
-
-    // Write the original data length as a 4-byte little-endian header
-    let original_length = 13u32;
-    compressed_data.extend_from_slice(&original_length.to_le_bytes());
-
-    // Serialize the tree to the compressed data
-    let mut bit_stream = OutputBitStream::new(&mut compressed_data);
+    // Serialize the tree into the block payload
+    let mut payload = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut payload);
     serialize_tree_to_bits(&tree, &mut bit_stream).expect("Tree serialization should succeed");
 
     // Add the encoded data: "0110001011101000" = 0x62E8 (16 bits)
@@ -71,8 +103,8 @@ fn decompresses_bits_to_ab_sequence_with_two_node_tree() {
 
     bit_stream.flush().expect("Should flush successfully");
 
-    // Create input reader from compressed data
-    let cursor = Cursor::new(compressed_data);
+    // Create input reader from a stream holding that single block
+    let cursor = Cursor::new(build_stream(&[(13, payload)]));
 
     // Output stream to write decompressed data
     let mut output = Vec::new();
@@ -84,3 +116,316 @@ fn decompresses_bits_to_ab_sequence_with_two_node_tree() {
     let expected = b"ABBAAABABBBAB".to_vec();
     assert_eq!(output, expected);
 }
+
+#[test]
+fn rejects_input_without_magic_number() {
+    // Arrange: the legacy layout, a bare 4-byte length followed by a single leaf tree
+    let compressed_data = vec![10, 0, 0, 0, 0xA0, 0x80];
+    let mut output = Vec::new();
+
+    // Act
+    let error = decompress(Cursor::new(compressed_data), &mut output)
+        .expect_err("Decompression should reject data without the magic number");
+
+    // Assert
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn rejects_unsupported_format_version() {
+    // Arrange: a valid header whose version byte is from the future
+    let mut header = FileHeader::new(0);
+    header.version += 1;
+    let mut compressed_data = Vec::new();
+    write_file_header(&mut compressed_data, &header).expect("Header should be written");
+    compressed_data.push(0);
+    let mut output = Vec::new();
+
+    // Act
+    let error = decompress(Cursor::new(compressed_data), &mut output)
+        .expect_err("Decompression should reject an unknown version");
+
+    // Assert
+    assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn rejects_block_type_of_another_method() {
+    // Arrange: relabel a static Huffman stream as adaptive; the method is the last header byte
+    let mut compressed_data = Vec::new();
+    compress(Cursor::new(b"abababab"), &mut compressed_data).expect("Compression should succeed");
+    compressed_data[6] = METHOD_ADAPTIVE_HUFFMAN;
+    let mut output = Vec::new();
+
+    // Act
+    let error = decompress(Cursor::new(compressed_data), &mut output)
+        .expect_err("Decompression should reject a canonical block in an adaptive stream");
+
+    // Assert
+    assert!(matches!(error, HuffmanError::CorruptBlock(_)));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn rejects_payload_with_flipped_bit() {
+    // Arrange: "abababab" is a single block with one bit per symbol, so the data bits
+    // end the payload; the first bit of the payload's last byte is data for any code
+    // table shorter than 7 bytes. The payload follows the 7-byte file header and the
+    // 9-byte block header
+    let mut compressed_data = Vec::new();
+    compress(Cursor::new(b"abababab"), &mut compressed_data).expect("Compression should succeed");
+    let payload_length = single_block_payload_length(&compressed_data);
+    let last_payload_byte = 7 + 9 + payload_length - 1;
+    compressed_data[last_payload_byte] ^= 0x80;
+    let mut output = Vec::new();
+
+    // Act
+    let error = decompress(Cursor::new(compressed_data), &mut output)
+        .expect_err("Decompression should detect the corrupted payload");
+
+    // Assert
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("Checksum mismatch"));
+}
+
+#[test]
+fn rejects_stream_whose_trailer_length_disagrees_with_blocks() {
+    // Arrange: drop the only block, leaving the end marker and the original trailer
+    let mut compressed_data = Vec::new();
+    compress(Cursor::new(b"abababab"), &mut compressed_data).expect("Compression should succeed");
+    let payload_length = single_block_payload_length(&compressed_data);
+    compressed_data.drain(7..7 + 9 + payload_length);
+    let mut output = Vec::new();
+
+    // Act
+    let error = decompress(Cursor::new(compressed_data), &mut output)
+        .expect_err("Decompression should detect the missing block");
+
+    // Assert
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("Length mismatch"));
+}
+
+#[test]
+fn checks_the_full_64_bit_original_length() {
+    // Arrange: trailers declaring lengths beyond 4 GiB that wrap to the real length in 32 bits
+    let cases: [(&[u8], u64); 2] = [(b"", u32::MAX as u64 + 1), (b"A", u32::MAX as u64 + 2)];
+
+    cases.iter().for_each(|&(input, declared_length)| {
+        let mut compressed_data = Vec::new();
+        compress(Cursor::new(input), &mut compressed_data).expect("Compression should succeed");
+        // The trailer ends with the 8-byte length and the 4-byte checksum
+        let length_position = compressed_data.len() - 12;
+        compressed_data[length_position..length_position + 8]
+            .copy_from_slice(&declared_length.to_le_bytes());
+
+        // Act
+        let error = decompress(Cursor::new(compressed_data), &mut Vec::new())
+            .expect_err("Decompression should notice the length is not the one decoded");
+
+        // Assert
+        assert!(matches!(
+            error,
+            HuffmanError::LengthMismatch { expected, actual }
+                if expected == declared_length && actual == input.len() as u64
+        ));
+    });
+}
+
+#[test]
+fn empty_input_round_trips_through_header_and_trailer_only() {
+    // Arrange: empty input produces no blocks at all
+    let mut compressed_data = Vec::new();
+    compress(Cursor::new(Vec::new()), &mut compressed_data).expect("Compression should succeed");
+    let mut output = Vec::new();
+
+    // Act
+    decompress(Cursor::new(&compressed_data), &mut output).expect("Decompression should succeed");
+
+    // Assert: 7-byte header, 1-byte end marker, 8-byte length and 4-byte checksum
+    assert_eq!(compressed_data.len(), 20);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn rejects_stored_block_shorter_than_declared() {
+    // Arrange: a stored block claiming 5 bytes while carrying only 3
+    let mut compressed_data = Vec::new();
+    write_file_header(&mut compressed_data, &FileHeader::new(0)).expect("Header should be written");
+    let block_header = BlockHeader {
+        block_type: BLOCK_TYPE_STORED,
+        original_length: 5,
+        payload_length: 3,
+    };
+    write_block_header(&mut compressed_data, &block_header).expect("Block should be written");
+    compressed_data.extend_from_slice(b"abc");
+    let mut output = Vec::new();
+
+    // Act
+    let error = decompress(Cursor::new(compressed_data), &mut output)
+        .expect_err("Decompression should reject the inconsistent block");
+
+    // Assert
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+// Serializes a tree followed by the given data bytes into a block payload
+fn tree_payload(tree: &HuffmanNode, data: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut payload);
+    serialize_tree_to_bits(tree, &mut bit_stream).expect("Tree should serialize");
+    bit_stream.flush().expect("Bits should flush");
+    payload.extend_from_slice(data);
+    payload
+}
+
+#[test]
+fn refuses_stream_declaring_more_than_max_output_size() {
+    // Arrange: a two-byte single-leaf block that would expand to nearly 4 GiB
+    let compressed_data =
+        build_stream(&[(u32::MAX, tree_payload(&HuffmanNode::new_leaf(b'A', 1), &[]))]);
+    let options = DecompressionOptions {
+        max_output_size: Some(1 << 20),
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+
+    // Act
+    let error = decompress_with_options(Cursor::new(compressed_data), &mut output, &options)
+        .expect_err("Decompression should stop at the limit");
+
+    // Assert: refused before a single byte was produced
+    assert!(matches!(error, HuffmanError::LimitExceeded(_)));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn accepts_output_exactly_at_max_output_size() {
+    let compressed_data = build_stream(&[
+        (600, tree_payload(&HuffmanNode::new_leaf(b'A', 1), &[])),
+        (400, tree_payload(&HuffmanNode::new_leaf(b'B', 1), &[])),
+    ]);
+    let options = DecompressionOptions {
+        max_output_size: Some(1000),
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+
+    decompress_with_options(Cursor::new(compressed_data), &mut output, &options)
+        .expect("Decompression within the limit should succeed");
+
+    assert_eq!(output.len(), 1000);
+}
+
+#[test]
+fn forged_payload_length_is_reported_as_truncation() {
+    // Arrange: a block header claiming a 4 GiB payload followed by only three bytes
+    let mut compressed_data = Vec::new();
+    write_file_header(&mut compressed_data, &FileHeader::new(0)).expect("Header should be written");
+    let block_header = BlockHeader {
+        block_type: BLOCK_TYPE_STORED,
+        original_length: u32::MAX,
+        payload_length: u32::MAX,
+    };
+    write_block_header(&mut compressed_data, &block_header).expect("Block should be written");
+    compressed_data.extend_from_slice(b"abc");
+
+    // Act
+    let error = decompress(Cursor::new(compressed_data), &mut Vec::new())
+        .expect_err("Decompression should reject the short payload");
+
+    // Assert
+    assert!(matches!(error, HuffmanError::TruncatedStream));
+}
+
+#[test]
+fn rejects_coded_block_declaring_more_symbols_than_payload_bits() {
+    // Arrange: a two-leaf tree needs at least one bit per symbol, but the payload has
+    // room for a few dozen at most
+    let tree = HuffmanNode::new_internal(
+        HuffmanNode::new_leaf(b'A', 1),
+        HuffmanNode::new_leaf(b'B', 1),
+    );
+    let compressed_data = build_stream(&[(1_000_000, tree_payload(&tree, &[0x5A; 4]))]);
+
+    // Act
+    let error = decompress(Cursor::new(compressed_data), &mut Vec::new())
+        .expect_err("Decompression should reject the impossible block");
+
+    // Assert
+    assert!(matches!(error, HuffmanError::CorruptBlock(_)));
+}
+
+// Compresses with a block index, several small blocks and a few threads
+fn compressed_with_index(input: &[u8]) -> Vec<u8> {
+    let options = CompressionOptions {
+        block_size: 1000,
+        threads: 3,
+        block_index: true,
+        ..Default::default()
+    };
+    let mut compressed_data = Vec::new();
+    compress_with_options(input, &mut compressed_data, &options)
+        .expect("Compression should succeed");
+    compressed_data
+}
+
+#[test]
+fn parallel_and_sequential_decoding_give_identical_bytes() {
+    let input: Vec<u8> = (0..25_000u32).map(|i| (i * 7 % 97) as u8).collect();
+    let compressed_data = compressed_with_index(&input);
+    let options = DecompressionOptions {
+        threads: 4,
+        ..Default::default()
+    };
+
+    let mut sequential = Vec::new();
+    decompress(compressed_data.as_slice(), &mut sequential).expect("Sequential should succeed");
+    let mut parallel = Vec::new();
+    decompress_parallel(Cursor::new(&compressed_data), &mut parallel, &options)
+        .expect("Parallel should succeed");
+
+    assert_eq!(parallel, sequential);
+    assert_eq!(parallel, input);
+}
+
+#[test]
+fn parallel_decoding_falls_back_to_sequential_without_an_index() {
+    let input = b"no index was written for this stream".repeat(50);
+    let mut compressed_data = Vec::new();
+    compress(input.as_slice(), &mut compressed_data).expect("Compression should succeed");
+    let options = DecompressionOptions {
+        threads: 4,
+        ..Default::default()
+    };
+
+    let mut output = Vec::new();
+    decompress_parallel(Cursor::new(&compressed_data), &mut output, &options)
+        .expect("Decompression should succeed");
+
+    assert_eq!(output, input);
+}
+
+#[test]
+fn both_decoders_reject_an_index_that_disagrees_with_the_blocks() {
+    // Arrange: shrink the last entry's original length, just before the 4-byte count
+    let input: Vec<u8> = (0..5000u32).map(|i| (i % 31) as u8).collect();
+    let mut compressed_data = compressed_with_index(&input);
+    let length_position = compressed_data.len() - 8;
+    compressed_data[length_position] ^= 0x01;
+    let options = DecompressionOptions {
+        threads: 2,
+        ..Default::default()
+    };
+
+    // Act
+    let sequential_error = decompress(compressed_data.as_slice(), &mut Vec::new()).unwrap_err();
+    let parallel_error =
+        decompress_parallel(Cursor::new(&compressed_data), &mut Vec::new(), &options).unwrap_err();
+
+    // Assert
+    assert!(matches!(sequential_error, HuffmanError::CorruptIndex(_)));
+    assert!(matches!(parallel_error, HuffmanError::CorruptIndex(_)));
+}
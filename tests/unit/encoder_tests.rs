@@ -0,0 +1,153 @@
+use crate::common::block_lengths;
+use std::io::Write;
+use tdd_huffman::file_header::FILE_HEADER_SIZE;
+use tdd_huffman::{compress, decompress, CompressionOptions, HuffmanEncoder};
+
+// Writer that refuses a single write once it holds fail_at bytes, then works again
+struct FlakyWriter {
+    written: Vec<u8>,
+    fail_at: Option<usize>,
+}
+
+impl Write for FlakyWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.fail_at == Some(self.written.len()) {
+            self.fail_at = None;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::WouldBlock,
+                "try again",
+            ));
+        }
+        self.written.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn matches_one_shot_compression_when_written_piecemeal() {
+    let input: Vec<u8> = (0..5000).map(|i| b"piecemeal"[i % 9]).collect();
+    let mut expected = Vec::new();
+    compress(input.as_slice(), &mut expected).expect("Compression should succeed");
+
+    let mut encoder = HuffmanEncoder::new(Vec::new());
+    input
+        .chunks(37)
+        .for_each(|chunk| encoder.write_all(chunk).expect("Write should succeed"));
+    let compressed_data = encoder.finish().expect("Finish should succeed");
+
+    assert_eq!(compressed_data, expected);
+}
+
+#[test]
+fn accepts_data_through_io_copy() {
+    let input = b"copied into the encoder like any other writer".repeat(20);
+    let mut encoder = HuffmanEncoder::new(Vec::new());
+
+    std::io::copy(&mut input.as_slice(), &mut encoder).expect("Copy should succeed");
+    let compressed_data = encoder.finish().expect("Finish should succeed");
+
+    let mut output = Vec::new();
+    decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+    assert_eq!(output, input);
+}
+
+#[test]
+fn emits_full_blocks_as_they_fill() {
+    let options = CompressionOptions {
+        block_size: 100,
+        ..Default::default()
+    };
+    let mut encoder =
+        HuffmanEncoder::with_options(Vec::new(), options).expect("Options should be valid");
+
+    encoder.write_all(&[b'q'; 250]).unwrap();
+    let written_before_finish = encoder.get_ref().len();
+    let compressed_data = encoder.finish().unwrap();
+
+    assert!(written_before_finish > 0);
+    assert_eq!(block_lengths(&compressed_data), vec![100, 100, 50]);
+}
+
+#[test]
+fn flush_emits_a_short_block_that_can_be_decoded() {
+    let mut encoder = HuffmanEncoder::new(Vec::new());
+
+    encoder.write_all(b"first message").unwrap();
+    encoder.flush().unwrap();
+    encoder.flush().unwrap();
+    encoder.write_all(b", second message").unwrap();
+    let compressed_data = encoder.finish().unwrap();
+
+    assert_eq!(block_lengths(&compressed_data), vec![13, 16]);
+    let mut output = Vec::new();
+    decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+    assert_eq!(output, b"first message, second message");
+}
+
+#[test]
+fn finishing_without_writes_produces_an_empty_stream() {
+    let compressed_data = HuffmanEncoder::new(Vec::new()).finish().unwrap();
+
+    let mut expected = Vec::new();
+    compress(std::io::empty(), &mut expected).unwrap();
+    assert_eq!(compressed_data, expected);
+}
+
+#[test]
+fn rejects_invalid_options() {
+    let options = CompressionOptions {
+        block_size: 0,
+        ..Default::default()
+    };
+
+    let error = HuffmanEncoder::with_options(Vec::new(), options)
+        .err()
+        .expect("A zero block size cannot make progress");
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn retried_flush_counts_the_data_once() {
+    let writer = FlakyWriter {
+        written: Vec::new(),
+        fail_at: Some(FILE_HEADER_SIZE as usize),
+    };
+    let mut encoder = HuffmanEncoder::new(writer);
+
+    encoder.write_all(b"flushed twice").unwrap();
+    assert!(encoder.flush().is_err());
+    encoder.flush().unwrap();
+    let compressed_data = encoder.finish().unwrap().written;
+
+    // The trailer's length and CRC-32 would not match if the batch were counted twice
+    let mut output = Vec::new();
+    decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+    assert_eq!(output, b"flushed twice");
+}
+
+#[test]
+fn failed_write_takes_none_of_the_data() {
+    let options = CompressionOptions {
+        block_size: 4,
+        ..Default::default()
+    };
+    let writer = FlakyWriter {
+        written: Vec::new(),
+        fail_at: Some(FILE_HEADER_SIZE as usize),
+    };
+    let mut encoder = HuffmanEncoder::with_options(writer, options).unwrap();
+
+    assert_eq!(encoder.write(b"abcd").unwrap(), 4);
+    assert!(encoder.write(b"efgh").is_err());
+    assert!(encoder.flush().is_ok());
+    assert_eq!(encoder.write(b"efgh").unwrap(), 4);
+    let compressed_data = encoder.finish().unwrap().written;
+
+    let mut output = Vec::new();
+    decompress(compressed_data.as_slice(), &mut output).expect("Decompression should succeed");
+    assert_eq!(output, b"abcdefgh");
+}
@@ -0,0 +1,68 @@
+use crate::common::{decoded_with, encoded_with};
+use std::io::Cursor;
+use tdd_huffman::{
+    encode_block_with, AdaptiveHuffmanModel, ByteFrequencyMap, CompressionOptions,
+    ContextHuffmanCoder, EntropyCoder, HuffmanCoder, InputBitStream, RangeCoder, TansCoder,
+};
+
+// Blocks every coder has to give back: text, every byte value, a skewed mix and one symbol
+fn sample_blocks() -> Vec<Vec<u8>> {
+    vec![
+        b"the quick brown fox jumped over the lazy dog".to_vec(),
+        b"the quick brown fox jumps over the lazy dog, "
+            .iter()
+            .copied()
+            .cycle()
+            .take(20_000)
+            .collect(),
+        (0..=255u8).cycle().take(10_000).collect(),
+        (0..5000u32).map(|i| (i * i % 61) as u8).collect(),
+        vec![b'z'; 1000],
+    ]
+}
+
+// Checks that coder C decodes every sample block it encoded, a whole block at a time and
+// symbol by symbol, and that an empty block gets neither a model nor a payload
+fn assert_round_trips<C: EntropyCoder>() {
+    sample_blocks().iter().for_each(|block| {
+        let payload = encoded_with::<C>(block);
+        let mut bit_stream = InputBitStream::new(Cursor::new(&payload));
+        let mut coder = C::deserialize_model(&mut bit_stream).unwrap();
+        let by_symbol: Vec<u8> = block
+            .iter()
+            .map(|_| coder.decode_symbol(&mut bit_stream).unwrap())
+            .collect();
+
+        assert_eq!(&decoded_with::<C>(&payload, block.len()), block);
+        assert_eq!(&by_symbol, block);
+    });
+
+    let options = CompressionOptions::default();
+    assert!(C::from_frequencies(&ByteFrequencyMap::new(), &options).is_none());
+    assert!(encode_block_with::<C>(b"", &options).unwrap().is_empty());
+}
+
+#[test]
+fn huffman_coder_decodes_what_it_encoded() {
+    assert_round_trips::<HuffmanCoder>();
+}
+
+#[test]
+fn context_huffman_coder_decodes_what_it_encoded() {
+    assert_round_trips::<ContextHuffmanCoder>();
+}
+
+#[test]
+fn adaptive_huffman_model_decodes_what_it_encoded() {
+    assert_round_trips::<AdaptiveHuffmanModel>();
+}
+
+#[test]
+fn range_coder_decodes_what_it_encoded() {
+    assert_round_trips::<RangeCoder>();
+}
+
+#[test]
+fn tans_coder_decodes_what_it_encoded() {
+    assert_round_trips::<TansCoder>();
+}
@@ -0,0 +1,130 @@
+use std::io::{Cursor, Read, Write};
+use tdd_huffman::{
+    compress, compress_with_options, decompress, deserialize_tree, write_file_header,
+    CompressionOptions, FileHeader, HuffmanDecoder, HuffmanError, InputBitStream,
+};
+
+fn compressed(data: &[u8]) -> Vec<u8> {
+    let mut compressed_data = Vec::new();
+    compress(Cursor::new(data), &mut compressed_data).expect("Compression should succeed");
+    compressed_data
+}
+
+// Writer that refuses every write
+struct FailingWriter;
+
+impl Write for FailingWriter {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::BrokenPipe,
+            "closed",
+        ))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn foreign_data_is_reported_as_bad_magic() {
+    let error = decompress(Cursor::new(b"GZIP\x03\x00".to_vec()), &mut Vec::new()).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::BadMagic(magic) if magic == *b"GZIP"));
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn future_version_is_reported_with_its_number() {
+    let mut header = FileHeader::new(0);
+    header.version += 1;
+    let mut compressed_data = Vec::new();
+    write_file_header(&mut compressed_data, &header).unwrap();
+
+    let error = decompress(Cursor::new(compressed_data), &mut Vec::new()).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::UnsupportedVersion(v) if v == header.version));
+}
+
+#[test]
+fn cut_off_stream_is_reported_as_truncated() {
+    let mut compressed_data = compressed(b"hello, hello, hello");
+    compressed_data.truncate(compressed_data.len() - 3);
+
+    let error = decompress(Cursor::new(compressed_data), &mut Vec::new()).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::TruncatedStream));
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn corrupted_data_is_reported_as_checksum_mismatch() {
+    // The last 12 bytes are the trailer: 8 bytes of length, then the CRC-32
+    let mut compressed_data = compressed(b"abababab");
+    let checksum_byte = compressed_data.len() - 1;
+    compressed_data[checksum_byte] ^= 0xFF;
+
+    let error = decompress(Cursor::new(compressed_data), &mut Vec::new()).unwrap_err();
+
+    assert!(matches!(
+        error,
+        HuffmanError::ChecksumMismatch { expected, actual } if expected != actual
+    ));
+}
+
+#[test]
+fn empty_tree_data_is_reported_as_truncated() {
+    let mut bit_stream = InputBitStream::new(Cursor::new(Vec::new()));
+
+    let error = deserialize_tree(&mut bit_stream).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::TruncatedStream));
+}
+
+#[test]
+fn out_of_range_options_are_reported_as_invalid_options() {
+    let options = CompressionOptions {
+        block_size: 0,
+        ..Default::default()
+    };
+
+    let error = compress_with_options(Cursor::new(b"abc"), &mut Vec::new(), &options).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::InvalidOptions(_)));
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn output_failures_keep_the_original_io_error() {
+    let error = compress(Cursor::new(b"abc"), &mut FailingWriter).unwrap_err();
+
+    match error {
+        HuffmanError::Io(inner) => assert_eq!(inner.kind(), std::io::ErrorKind::BrokenPipe),
+        other => panic!("Expected an Io error, got {other:?}"),
+    }
+}
+
+#[test]
+fn cause_survives_the_round_trip_through_io_error() {
+    let io_error: std::io::Error = HuffmanError::UnsupportedFlags(0x80).into();
+    assert_eq!(io_error.kind(), std::io::ErrorKind::Unsupported);
+
+    let error = HuffmanError::from(io_error);
+
+    assert!(matches!(error, HuffmanError::UnsupportedFlags(0x80)));
+}
+
+#[test]
+fn decoder_read_errors_convert_back_to_the_cause() {
+    let mut compressed_data = compressed(b"abababab");
+    let checksum_byte = compressed_data.len() - 1;
+    compressed_data[checksum_byte] ^= 0xFF;
+    let mut decoder = HuffmanDecoder::new(Cursor::new(compressed_data));
+
+    let io_error = decoder.read_to_end(&mut Vec::new()).unwrap_err();
+
+    assert!(matches!(
+        HuffmanError::from(io_error),
+        HuffmanError::ChecksumMismatch { .. }
+    ));
+}
@@ -0,0 +1,65 @@
+use std::io::Cursor;
+use tdd_huffman::file_header::{FLAG_CRC32, FORMAT_VERSION, MAGIC, METHOD_HUFFMAN};
+use tdd_huffman::{read_file_header, write_file_header, FileHeader, HuffmanError};
+
+#[test]
+fn writes_magic_version_flags_and_method() {
+    let mut output = Vec::new();
+
+    write_file_header(&mut output, &FileHeader::new(FLAG_CRC32)).unwrap();
+
+    let mut expected = MAGIC.to_vec();
+    expected.extend_from_slice(&[FORMAT_VERSION, FLAG_CRC32, METHOD_HUFFMAN]);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn reads_back_written_header() {
+    let header = FileHeader::new(FLAG_CRC32);
+    let mut output = Vec::new();
+    write_file_header(&mut output, &header).unwrap();
+
+    let read_back = read_file_header(&mut Cursor::new(output)).unwrap();
+
+    assert_eq!(read_back, header);
+    assert!(read_back.has_checksum());
+}
+
+#[test]
+fn rejects_unknown_magic_number() {
+    let data = b"GZIP\x03\x00".to_vec();
+
+    let error = read_file_header(&mut Cursor::new(data)).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_unknown_flags() {
+    let mut output = Vec::new();
+    write_file_header(&mut output, &FileHeader::new(0x80)).unwrap();
+
+    let error = read_file_header(&mut Cursor::new(output)).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[test]
+fn rejects_unknown_method() {
+    let mut output = Vec::new();
+    write_file_header(&mut output, &FileHeader::new(FLAG_CRC32).with_method(0x7F)).unwrap();
+
+    let error = read_file_header(&mut Cursor::new(output)).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::UnsupportedMethod(0x7F)));
+    assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[test]
+fn reports_truncated_header_as_unexpected_eof() {
+    let data = MAGIC.to_vec();
+
+    let error = read_file_header(&mut Cursor::new(data)).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
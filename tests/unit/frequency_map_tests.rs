@@ -1,5 +1,6 @@
 use std::io::Cursor;
-use tdd_huffman::count_frequencies;
+use tdd_huffman::frequency_map::INITIAL_CONTEXT;
+use tdd_huffman::{count_context_frequencies, count_frequencies};
 
 #[test]
 fn count_single_byte_occurrence() {
@@ -47,3 +48,18 @@ fn handle_empty_input_gracefully() {
     let (frequencies2, _) = count_frequencies(Cursor::new(&[])).unwrap();
     assert_eq!(frequencies, frequencies2);
 }
+
+#[test]
+fn count_bytes_under_the_byte_before_them() {
+    let contexts = count_context_frequencies(b"abab");
+
+    assert_eq!(contexts.len(), 256);
+    assert_eq!(contexts[INITIAL_CONTEXT as usize].get(&b'a'), Some(&1));
+    assert_eq!(contexts[b'a' as usize].get(&b'b'), Some(&2));
+    assert_eq!(contexts[b'b' as usize].get(&b'a'), Some(&1));
+    assert!(contexts[b'c' as usize].is_empty());
+    assert_eq!(
+        contexts.iter().flat_map(|map| map.values()).sum::<usize>(),
+        4
+    );
+}
@@ -0,0 +1,86 @@
+use std::io::Cursor;
+use tdd_huffman::{
+    count_frequencies, deserialize_frequencies, normalize_frequencies, serialize_frequencies,
+    HuffmanError, InputBitStream, OutputBitStream,
+};
+
+fn normalized(input: &[u8], scale_bits: u32) -> [u32; 256] {
+    let (frequency_map, _) = count_frequencies(input).unwrap();
+    normalize_frequencies(&frequency_map, scale_bits).expect("Input should not be empty")
+}
+
+fn serialized(frequencies: &[u32; 256]) -> Vec<u8> {
+    let mut serialized = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut serialized);
+    serialize_frequencies(frequencies, &mut bit_stream).unwrap();
+    bit_stream.flush().unwrap();
+    serialized
+}
+
+#[test]
+fn empty_map_has_no_frequencies() {
+    let (frequency_map, _) = count_frequencies(b"".as_slice()).unwrap();
+
+    assert_eq!(normalize_frequencies(&frequency_map, 12), None);
+}
+
+#[test]
+fn frequencies_add_up_to_any_scale() {
+    let input = b"the quick brown fox jumped over the lazy dog";
+
+    [8, 12, 16].iter().for_each(|&scale_bits| {
+        let frequencies = normalized(input, scale_bits);
+
+        assert_eq!(frequencies.iter().sum::<u32>(), 1 << scale_bits);
+        assert!(frequencies[b' ' as usize] > frequencies[b'q' as usize]);
+        assert_eq!(frequencies[b'Z' as usize], 0);
+    });
+}
+
+#[test]
+fn many_rare_symbols_fit_a_small_table() {
+    // Half the byte values are rare and must each be raised to 1, which overshoots a 2^12
+    // table by more than any single frequency could give back
+    let input: Vec<u8> = (0..128u8)
+        .flat_map(|symbol| std::iter::repeat_n(symbol, 10_000))
+        .chain(128..=255u8)
+        .collect();
+
+    let frequencies = normalized(&input, 12);
+
+    assert!(frequencies.iter().all(|&frequency| frequency >= 1));
+    assert_eq!(frequencies.iter().sum::<u32>(), 1 << 12);
+}
+
+#[test]
+fn every_byte_value_fits_the_smallest_table() {
+    let input: Vec<u8> = (0..=255u8).chain([0u8; 1000]).collect();
+
+    let frequencies = normalized(&input, 8);
+
+    assert_eq!(frequencies, [1; 256]);
+}
+
+#[test]
+fn table_round_trips_at_its_own_scale() {
+    let frequencies = normalized(b"abracadabra", 12);
+
+    let read_back = deserialize_frequencies(
+        &mut InputBitStream::new(Cursor::new(serialized(&frequencies))),
+        12,
+    );
+
+    assert_eq!(read_back.unwrap(), frequencies);
+}
+
+#[test]
+fn rejects_a_table_of_another_scale() {
+    let frequencies = normalized(b"abracadabra", 12);
+
+    let error = deserialize_frequencies(
+        &mut InputBitStream::new(Cursor::new(serialized(&frequencies))),
+        16,
+    );
+
+    assert!(matches!(error, Err(HuffmanError::CorruptTree(_))));
+}
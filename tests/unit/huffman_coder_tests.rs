@@ -0,0 +1,58 @@
+use crate::common::{decoded_with, fibonacci_frequencies};
+use tdd_huffman::block_format::BLOCK_TYPE_CANONICAL;
+use tdd_huffman::{
+    compress, count_frequencies, encode_block_with, read_block_header, read_file_header,
+    CompressionOptions, EntropyCoder, HuffmanCoder, OutputBitStream,
+};
+
+#[test]
+fn payload_matches_the_block_written_by_compress() {
+    let input = b"abracadabra abracadabra";
+    let mut compressed_data = Vec::new();
+    compress(input.as_slice(), &mut compressed_data).unwrap();
+    let mut reader = compressed_data.as_slice();
+    read_file_header(&mut reader).unwrap();
+    let block_header = read_block_header(&mut reader).unwrap();
+
+    let payload = encode_block_with::<HuffmanCoder>(input, &CompressionOptions::default()).unwrap();
+
+    assert_eq!(block_header.block_type, BLOCK_TYPE_CANONICAL);
+    assert_eq!(payload, &reader[..block_header.payload_length as usize]);
+}
+
+#[test]
+fn single_symbol_block_stores_only_the_model() {
+    let options = CompressionOptions::default();
+    let (frequency_map, _) = count_frequencies(b"zzzz".as_slice()).unwrap();
+    let coder = HuffmanCoder::from_frequencies(&frequency_map, &options).unwrap();
+
+    let short = encode_block_with::<HuffmanCoder>(b"zzzz", &options).unwrap();
+    let long = encode_block_with::<HuffmanCoder>(&[b'z'; 10_000], &options).unwrap();
+
+    assert_eq!(short, long);
+    assert_eq!(coder.max_symbols(short.len()), None);
+    assert_eq!(
+        decoded_with::<HuffmanCoder>(&long, 10_000),
+        vec![b'z'; 10_000]
+    );
+}
+
+#[test]
+fn counts_too_skewed_for_64_bit_codes_get_limited_codes() {
+    // 80 Fibonacci counts would give a Huffman tree 79 levels deep
+    let frequency_map = fibonacci_frequencies(80);
+    let symbols: Vec<u8> = (0..80u8).collect();
+
+    let mut coder = HuffmanCoder::from_frequencies(&frequency_map, &CompressionOptions::default())
+        .expect("Counts are not empty");
+    let mut payload = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut payload);
+    coder.serialize_model(&mut bit_stream).unwrap();
+    coder.encode_symbols(&symbols, &mut bit_stream).unwrap();
+    bit_stream.flush().unwrap();
+
+    assert_eq!(
+        decoded_with::<HuffmanCoder>(&payload, symbols.len()),
+        symbols
+    );
+}
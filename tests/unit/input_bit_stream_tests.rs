@@ -215,3 +215,72 @@ fn read_from_stream_with_exact_bit_count() {
     // Note: In a real compression scenario, we'd stop here based on metadata
     // The remaining 7 bits are padding and shouldn't be read as meaningful data
 }
+
+#[test]
+fn peek_bits_does_not_consume_them() {
+    // Create a stream with two bytes: [10110010, 11010000]
+    let data = vec![178u8, 208u8];
+    let mut input_stream = create_bit_stream_from_bytes(&data);
+
+    // Peek the first 10 bits twice: 1011001011
+    assert_eq!(input_stream.peek_bits(10).unwrap(), 0b1011001011);
+    assert_eq!(input_stream.peek_bits(10).unwrap(), 0b1011001011);
+
+    // Reading bits afterwards still starts at the first bit
+    assert_bits_read_in_sequence(&mut input_stream, &[1, 0, 1]);
+}
+
+#[test]
+fn consume_skips_peeked_bits() {
+    // Create a stream with two bytes: [10110010, 11010000]
+    let data = vec![178u8, 208u8];
+    let mut input_stream = create_bit_stream_from_bytes(&data);
+
+    // Peek 4 bits (1011), consume 3 of them, then peek again: 1001011
+    input_stream.peek_bits(4).unwrap();
+    input_stream.consume(3).unwrap();
+    assert_eq!(input_stream.peek_bits(7).unwrap(), 0b1001011);
+
+    // Reading continues after the consumed bits: 1, 0, 0
+    assert_bits_read_in_sequence(&mut input_stream, &[1, 0, 0]);
+}
+
+#[test]
+fn peek_past_end_of_input_pads_with_zeros() {
+    // Create a stream with one byte: 10110010 (178)
+    let data = vec![178u8];
+    let mut input_stream = create_bit_stream_from_bytes(&data);
+
+    // Peeking 12 bits sees the 8 real bits followed by 4 zeros
+    assert_eq!(input_stream.peek_bits(12).unwrap(), 0b101100100000);
+
+    // The real bits can be consumed, the padding cannot
+    input_stream.consume(8).unwrap();
+    let error = input_stream.consume(1).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn read_bits_returns_whole_values_across_byte_boundaries() {
+    // Create a stream with three bytes: [10110010, 11010000, 11111111]
+    let data = vec![178u8, 208u8, 255u8];
+    let mut input_stream = create_bit_stream_from_bytes(&data);
+
+    // 3 bits: 101, 9 bits: 100101101, 12 bits: 000011111111
+    assert_eq!(input_stream.read_bits(3).unwrap(), 0b101);
+    assert_eq!(input_stream.read_bits(9).unwrap(), 0b100101101);
+    assert_eq!(input_stream.read_bits(12).unwrap(), 0b000011111111);
+
+    // The input is exhausted now
+    assert!(input_stream.read_bits(1).is_err());
+}
+
+#[test]
+fn read_bits_handles_full_64_bit_values() {
+    let data: Vec<u8> = (1..=9).collect();
+    let mut input_stream = create_bit_stream_from_bytes(&data);
+
+    assert_eq!(input_stream.read_bits(4).unwrap(), 0);
+    assert_eq!(input_stream.read_bits(64).unwrap(), 0x1020304050607080);
+    assert_eq!(input_stream.read_bits(4).unwrap(), 0x9);
+}
@@ -0,0 +1,110 @@
+use crate::common::{encoded_bits, fibonacci_frequencies};
+use tdd_huffman::{
+    build_huffman_tree, build_length_limited_huffman_tree, code_lengths_from_tree,
+    length_limited_code_lengths, ByteFrequencyMap,
+};
+
+#[test]
+fn unlimited_huffman_tree_exceeds_the_limit_for_fibonacci_frequencies() {
+    let frequency_map = fibonacci_frequencies(30);
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+
+    let longest = code_lengths_from_tree(&tree).into_iter().max().unwrap();
+
+    assert_eq!(longest, 29);
+}
+
+#[test]
+fn limits_code_lengths_for_fibonacci_frequencies() {
+    let frequency_map = fibonacci_frequencies(30);
+
+    let lengths =
+        length_limited_code_lengths(&frequency_map, 12).expect("30 symbols fit in 12 bits");
+
+    assert_eq!(lengths.into_iter().max(), Some(12));
+    assert_eq!(lengths.iter().filter(|&&length| length > 0).count(), 30);
+}
+
+#[test]
+fn limited_lengths_form_a_complete_prefix_code() {
+    let frequency_map = fibonacci_frequencies(40);
+
+    let lengths =
+        length_limited_code_lengths(&frequency_map, 15).expect("40 symbols fit in 15 bits");
+
+    let kraft_sum: u64 = lengths
+        .iter()
+        .filter(|&&length| length > 0)
+        .map(|&length| 1u64 << (15 - length))
+        .sum();
+    assert_eq!(kraft_sum, 1 << 15);
+}
+
+#[test]
+fn matches_huffman_cost_when_the_limit_is_not_binding() {
+    let frequency_map: ByteFrequencyMap = [
+        (b'a', 45),
+        (b'b', 13),
+        (b'c', 12),
+        (b'd', 16),
+        (b'e', 9),
+        (b'f', 5),
+    ]
+    .into_iter()
+    .collect();
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+
+    let lengths =
+        length_limited_code_lengths(&frequency_map, 15).expect("6 symbols fit in 15 bits");
+
+    assert_eq!(
+        encoded_bits(&lengths, &frequency_map),
+        encoded_bits(&code_lengths_from_tree(&tree), &frequency_map)
+    );
+}
+
+#[test]
+fn uses_fixed_length_codes_when_the_limit_is_tight() {
+    // Four symbols within a 2-bit limit leave only one possible code
+    let frequency_map: ByteFrequencyMap = [(b'a', 1000), (b'b', 100), (b'c', 10), (b'd', 1)]
+        .into_iter()
+        .collect();
+
+    let lengths = length_limited_code_lengths(&frequency_map, 2).expect("4 symbols fit in 2 bits");
+
+    [b'a', b'b', b'c', b'd']
+        .iter()
+        .for_each(|&symbol| assert_eq!(lengths[symbol as usize], 2));
+}
+
+#[test]
+fn single_symbol_gets_a_one_bit_code() {
+    let frequency_map: ByteFrequencyMap = [(b'x', 7)].into_iter().collect();
+
+    let lengths = length_limited_code_lengths(&frequency_map, 8).expect("One symbol always fits");
+
+    assert_eq!(lengths[b'x' as usize], 1);
+    assert_eq!(lengths.iter().filter(|&&length| length > 0).count(), 1);
+}
+
+#[test]
+fn returns_none_when_the_symbols_do_not_fit() {
+    let frequency_map = fibonacci_frequencies(9);
+
+    assert!(length_limited_code_lengths(&frequency_map, 3).is_none());
+    assert!(length_limited_code_lengths(&ByteFrequencyMap::new(), 8).is_none());
+}
+
+#[test]
+fn limited_tree_keeps_the_total_frequency() {
+    let frequency_map = fibonacci_frequencies(30);
+    let total: usize = frequency_map.values().sum();
+
+    let tree =
+        build_length_limited_huffman_tree(&frequency_map, 15).expect("30 symbols fit in 15 bits");
+
+    assert_eq!(tree.frequency(), total);
+    assert!(code_lengths_from_tree(&tree)
+        .into_iter()
+        .all(|length| length <= 15));
+}
@@ -271,3 +271,46 @@ fn multiple_consecutive_flushes_emit_no_extra_bytes() {
     // Should emit exactly one padded byte: 10100000 = 160
     assert_eq!(output, vec![160u8]);
 }
+
+#[test]
+fn write_bits_emits_whole_values_most_significant_first() {
+    let mut output = Vec::new();
+    {
+        let mut bit_stream = OutputBitStream::new(&mut output);
+
+        // 3 bits: 101, 9 bits: 100101101 → 10110010 1101 (buffered)
+        bit_stream.write_bits(0b101, 3).unwrap();
+        bit_stream.write_bits(0b100101101, 9).unwrap();
+    }
+
+    // Only the first complete byte is emitted: 10110010 = 178
+    assert_eq!(output, vec![178u8]);
+}
+
+#[test]
+fn write_bits_ignores_bits_above_count() {
+    let mut output = Vec::new();
+    {
+        let mut bit_stream = OutputBitStream::new(&mut output);
+
+        // Only the lowest 4 bits (0110) of 0xF6 are written, then padded: 01100000 = 96
+        bit_stream.write_bits(0xF6, 4).unwrap();
+        bit_stream.flush().unwrap();
+    }
+
+    assert_eq!(output, vec![96u8]);
+}
+
+#[test]
+fn write_bits_handles_full_64_bit_values_after_pending_bits() {
+    let mut output = Vec::new();
+    {
+        let mut bit_stream = OutputBitStream::new(&mut output);
+
+        bit_stream.write_bits(0, 4).unwrap();
+        bit_stream.write_bits(0x1020304050607080, 64).unwrap();
+        bit_stream.write_bits(0x9, 4).unwrap();
+    }
+
+    assert_eq!(output, (1..=9).collect::<Vec<u8>>());
+}
@@ -0,0 +1,129 @@
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use tdd_huffman::{compress_with_options, read_range, CompressionOptions, HuffmanError};
+
+fn sample_input() -> Vec<u8> {
+    (0..10_000u32).map(|i| (i * 13 % 251) as u8).collect()
+}
+
+fn compressed(input: &[u8], block_index: bool) -> Vec<u8> {
+    let options = CompressionOptions {
+        block_size: 1000,
+        block_index,
+        ..Default::default()
+    };
+    let mut compressed_data = Vec::new();
+    compress_with_options(input, &mut compressed_data, &options)
+        .expect("Compression should succeed");
+    compressed_data
+}
+
+// Reader that counts how many bytes were actually read through it
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.bytes_read += count as u64;
+        Ok(count)
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(position)
+    }
+}
+
+#[test]
+fn reads_a_range_inside_one_block() {
+    let input = sample_input();
+    let compressed_data = compressed(&input, true);
+
+    let range = read_range(Cursor::new(&compressed_data), 2100, 50).unwrap();
+
+    assert_eq!(range, &input[2100..2150]);
+}
+
+#[test]
+fn reads_a_range_spanning_several_blocks() {
+    let input = sample_input();
+    let compressed_data = compressed(&input, true);
+
+    let range = read_range(Cursor::new(&compressed_data), 1500, 3200).unwrap();
+
+    assert_eq!(range, &input[1500..4700]);
+}
+
+#[test]
+fn cuts_ranges_short_at_the_end_of_the_data() {
+    let input = sample_input();
+    let compressed_data = compressed(&input, true);
+
+    let tail = read_range(Cursor::new(&compressed_data), 9990, 100).unwrap();
+    let beyond = read_range(Cursor::new(&compressed_data), 20_000, 100).unwrap();
+
+    assert_eq!(tail, &input[9990..]);
+    assert!(beyond.is_empty());
+}
+
+#[test]
+fn reads_a_range_from_a_stream_after_other_data() {
+    let input = sample_input();
+    let mut embedded = b"leading bytes".to_vec();
+    let start = embedded.len() as u64;
+    embedded.extend_from_slice(&compressed(&input, true));
+    let mut reader = Cursor::new(&embedded);
+    reader.set_position(start);
+
+    let range = read_range(reader, 4321, 1000).unwrap();
+
+    assert_eq!(range, &input[4321..5321]);
+}
+
+#[test]
+fn finds_blocks_by_scanning_headers_when_there_is_no_index() {
+    let input = sample_input();
+    let compressed_data = compressed(&input, false);
+
+    let range = read_range(Cursor::new(&compressed_data), 7777, 1234).unwrap();
+
+    assert_eq!(range, &input[7777..9011]);
+}
+
+#[test]
+fn reads_only_the_blocks_it_needs() {
+    let input = sample_input();
+    let compressed_data = compressed(&input, true);
+    let mut reader = CountingReader {
+        inner: Cursor::new(&compressed_data),
+        bytes_read: 0,
+    };
+
+    let range = read_range(&mut reader, 5000, 10).unwrap();
+
+    assert_eq!(range, &input[5000..5010]);
+    assert!(
+        reader.bytes_read * 4 < compressed_data.len() as u64,
+        "Read {} of {} bytes for one block",
+        reader.bytes_read,
+        compressed_data.len()
+    );
+}
+
+#[test]
+fn rejects_an_index_entry_that_does_not_match_its_block() {
+    // Arrange: the last entry's original length sits just before the 4-byte count
+    let input = sample_input();
+    let mut compressed_data = compressed(&input, true);
+    let length_position = compressed_data.len() - 8;
+    compressed_data[length_position] ^= 0x01;
+
+    // Act
+    let error = read_range(Cursor::new(&compressed_data), 9500, 10).unwrap_err();
+
+    // Assert
+    assert!(matches!(error, HuffmanError::CorruptIndex(_)));
+}
@@ -0,0 +1,113 @@
+use crate::common::{decoded_with, encoded_with};
+use std::io::Cursor;
+use tdd_huffman::range_coder::SCALE_BITS;
+use tdd_huffman::{
+    count_frequencies, deserialize_frequencies, encode_block_with, scale_frequencies,
+    serialize_frequencies, CompressionOptions, EntropyCoder, HuffmanCoder, HuffmanError,
+    InputBitStream, OutputBitStream, RangeCoder,
+};
+
+fn scaled(input: &[u8]) -> [u32; 256] {
+    let (frequency_map, _) = count_frequencies(input).unwrap();
+    scale_frequencies(&frequency_map).expect("Input should not be empty")
+}
+
+#[test]
+fn scaled_frequencies_add_up_to_the_total() {
+    let frequencies = scaled(b"abracadabra");
+
+    assert_eq!(frequencies.iter().sum::<u32>(), 1 << SCALE_BITS);
+    assert!(frequencies[b'a' as usize] > frequencies[b'b' as usize]);
+    assert_eq!(frequencies[b'z' as usize], 0);
+}
+
+#[test]
+fn rare_symbols_keep_a_nonzero_frequency() {
+    let mut input = vec![0u8; 1_000_000];
+    input[..255]
+        .iter_mut()
+        .zip(1..=255u8)
+        .for_each(|(byte, value)| *byte = value);
+
+    let frequencies = scaled(&input);
+
+    assert!(frequencies.iter().all(|&frequency| frequency >= 1));
+    assert_eq!(frequencies.iter().sum::<u32>(), 1 << SCALE_BITS);
+}
+
+#[test]
+fn single_symbol_takes_the_whole_total() {
+    let frequencies = scaled(b"zzzz");
+
+    assert_eq!(frequencies[b'z' as usize], 1 << SCALE_BITS);
+}
+
+#[test]
+fn frequency_table_round_trips() {
+    let frequencies = scaled(b"the quick brown fox jumped over the lazy dog");
+    let mut serialized = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut serialized);
+    serialize_frequencies(&frequencies, &mut bit_stream).unwrap();
+    bit_stream.flush().unwrap();
+
+    let read_back = deserialize_frequencies(
+        &mut InputBitStream::new(Cursor::new(serialized)),
+        SCALE_BITS,
+    );
+
+    assert_eq!(read_back.unwrap(), frequencies);
+}
+
+#[test]
+fn rejects_frequencies_that_do_not_add_up() {
+    let mut frequencies = scaled(b"abc");
+    frequencies[b'a' as usize] -= 1;
+    let mut serialized = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut serialized);
+    serialize_frequencies(&frequencies, &mut bit_stream).unwrap();
+    bit_stream.flush().unwrap();
+
+    let error = deserialize_frequencies(
+        &mut InputBitStream::new(Cursor::new(serialized)),
+        SCALE_BITS,
+    );
+
+    assert!(matches!(error, Err(HuffmanError::CorruptTree(_))));
+}
+
+#[test]
+fn skewed_data_costs_well_under_a_bit_per_byte() {
+    // 99% zero bytes: Huffman needs at least one bit for each of them
+    let input: Vec<u8> = (0..100_000u32)
+        .map(|i| if i % 100 == 0 { (i / 100) as u8 } else { 0 })
+        .collect();
+
+    let range_payload = encoded_with::<RangeCoder>(&input);
+    let huffman_payload =
+        encode_block_with::<HuffmanCoder>(&input, &CompressionOptions::default()).unwrap();
+
+    assert!(huffman_payload.len() * 8 >= input.len());
+    assert!(
+        range_payload.len() * 4 < huffman_payload.len(),
+        "Range coder took {} bytes, Huffman {}",
+        range_payload.len(),
+        huffman_payload.len()
+    );
+    assert_eq!(
+        decoded_with::<RangeCoder>(&range_payload, input.len()),
+        input
+    );
+}
+
+#[test]
+fn single_symbol_block_has_no_symbol_bound() {
+    let payload = encoded_with::<RangeCoder>(&[7u8; 10_000]);
+    let mut bit_stream = InputBitStream::new(Cursor::new(&payload));
+    let coder = RangeCoder::deserialize_model(&mut bit_stream).unwrap();
+
+    assert_eq!(coder.max_symbols(payload.len()), None);
+    assert_eq!(
+        decoded_with::<RangeCoder>(&payload, 10_000),
+        vec![7u8; 10_000]
+    );
+}
@@ -0,0 +1,89 @@
+use crate::common::{decoded_with, encoded_with};
+use std::io::Cursor;
+use tdd_huffman::tans_coder::TABLE_LOG;
+use tdd_huffman::{
+    count_frequencies, encode_block_with, CompressionOptions, EntropyCoder, HuffmanCoder,
+    InputBitStream, OutputBitStream, RangeCoder, TansCoder,
+};
+
+#[test]
+fn normalized_frequencies_fill_the_table() {
+    let (frequency_map, _) = count_frequencies(b"abracadabra".as_slice()).unwrap();
+
+    let coder =
+        TansCoder::from_frequencies(&frequency_map, &CompressionOptions::default()).unwrap();
+
+    assert_eq!(coder.frequencies().iter().sum::<u32>(), 1 << TABLE_LOG);
+    assert_eq!(coder.frequencies()[b'z' as usize], 0);
+}
+
+#[test]
+fn symbols_fed_one_at_a_time_encode_like_a_whole_block() {
+    let input = b"the quick brown fox jumped over the lazy dog";
+    let (frequency_map, _) = count_frequencies(input.as_slice()).unwrap();
+    let options = CompressionOptions::default();
+    let mut coder = TansCoder::from_frequencies(&frequency_map, &options).unwrap();
+    let mut payload = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut payload);
+
+    coder.serialize_model(&mut bit_stream).unwrap();
+    input
+        .iter()
+        .for_each(|&symbol| coder.encode_symbol(symbol, &mut bit_stream).unwrap());
+    coder.encode_symbols(&[], &mut bit_stream).unwrap();
+    bit_stream.flush().unwrap();
+
+    assert_eq!(payload, encoded_with::<TansCoder>(input));
+}
+
+#[test]
+fn skewed_data_costs_close_to_the_range_coder() {
+    // 99% zero bytes: Huffman needs at least one bit for each of them, while tANS only loses
+    // the share of its table that rare symbols hold beyond their real probability
+    let input: Vec<u8> = (0..100_000u32)
+        .map(|i| if i % 100 == 0 { (i / 100) as u8 } else { 0 })
+        .collect();
+    let options = CompressionOptions::default();
+
+    let tans_payload = encoded_with::<TansCoder>(&input);
+    let range_payload = encode_block_with::<RangeCoder>(&input, &options).unwrap();
+    let huffman_payload = encode_block_with::<HuffmanCoder>(&input, &options).unwrap();
+
+    assert!(
+        tans_payload.len() * 4 < huffman_payload.len(),
+        "tANS took {} bytes, Huffman {}",
+        tans_payload.len(),
+        huffman_payload.len()
+    );
+    assert!(
+        tans_payload.len() * 4 < range_payload.len() * 5,
+        "tANS took {} bytes, range coder {}",
+        tans_payload.len(),
+        range_payload.len()
+    );
+    assert_eq!(decoded_with::<TansCoder>(&tans_payload, input.len()), input);
+}
+
+#[test]
+fn single_symbol_block_stores_only_the_model_and_state() {
+    let short = encoded_with::<TansCoder>(b"zzzz");
+    let long = encoded_with::<TansCoder>(&[b'z'; 10_000]);
+    let mut bit_stream = InputBitStream::new(Cursor::new(&long));
+    let coder = TansCoder::deserialize_model(&mut bit_stream).unwrap();
+
+    assert_eq!(short, long);
+    assert_eq!(coder.max_symbols(long.len()), None);
+    assert_eq!(decoded_with::<TansCoder>(&long, 10_000), vec![b'z'; 10_000]);
+}
+
+#[test]
+fn balanced_block_is_bounded_by_its_payload() {
+    let payload = encoded_with::<TansCoder>(b"abababababcdcdcd");
+    let mut bit_stream = InputBitStream::new(Cursor::new(&payload));
+    let coder = TansCoder::deserialize_model(&mut bit_stream).unwrap();
+
+    assert_eq!(
+        coder.max_symbols(payload.len()),
+        Some(payload.len() as u64 * 8)
+    );
+}
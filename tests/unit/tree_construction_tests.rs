@@ -95,12 +95,21 @@ fn merge_two_non_leaf_nodes() {
     assert!(right_child.right_child().is_some());
 }
 
+#[test]
+fn empty_frequency_map_creates_no_tree() {
+    let frequency_map = ByteFrequencyMap::new();
+
+    let tree = build_huffman_tree(&frequency_map);
+
+    assert!(tree.is_none());
+}
+
 #[test]
 fn single_byte_creates_tree_with_one_leaf_node() {
     let mut frequency_map = ByteFrequencyMap::new();
     frequency_map.insert(65u8, 5usize); // 'A' appears 5 times
 
-    let tree = build_huffman_tree(&frequency_map);
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
 
     // For a single byte, the tree should be just one leaf node
     assert_eq!(tree.frequency(), 5);
@@ -116,7 +125,7 @@ fn two_bytes_create_tree_with_one_internal_node_and_two_leaves() {
     frequency_map.insert(65u8, 3usize); // 'A' appears 3 times
     frequency_map.insert(66u8, 7usize); // 'B' appears 7 times
 
-    let tree = build_huffman_tree(&frequency_map);
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
 
     // Root should have total frequency (3 + 7 = 10)
     assert_eq!(tree.frequency(), 10);
@@ -153,7 +162,7 @@ fn multiple_bytes_create_proper_binary_tree_structure() {
     frequency_map.insert(67u8, 1usize); // 'C' appears 1 time
     frequency_map.insert(68u8, 3usize); // 'D' appears 3 times
 
-    let tree = build_huffman_tree(&frequency_map);
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
 
     // Root should have total frequency (5 + 2 + 1 + 3 = 11)
     assert_eq!(tree.frequency(), 11);
@@ -187,3 +196,39 @@ fn multiple_bytes_create_proper_binary_tree_structure() {
         [65u8, 66u8, 67u8, 68u8].into_iter().collect()
     );
 }
+
+#[test]
+fn equal_frequencies_merge_lowest_symbols_first() {
+    let frequency_map: ByteFrequencyMap = [(b'd', 1), (b'c', 1), (b'b', 1), (b'a', 1)]
+        .into_iter()
+        .collect();
+
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+
+    // 'a' and 'b' are merged first, then 'c' and 'd'; the 'a'/'b' pair then sorts first
+    let expected = merge_nodes(
+        merge_leaf_nodes((b'a', 1), (b'b', 1)),
+        merge_leaf_nodes((b'c', 1), (b'd', 1)),
+    );
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn leaf_sorts_before_subtree_with_equal_frequency_and_higher_symbols() {
+    // After 'b' and 'c' merge into a node of frequency 2, 'a' ties with it
+    // and wins the tie because its symbol is lower
+    let frequency_map: ByteFrequencyMap = [(b'a', 2), (b'b', 1), (b'c', 1), (b'd', 5)]
+        .into_iter()
+        .collect();
+
+    let tree = build_huffman_tree(&frequency_map).expect("Frequency map is not empty");
+
+    let expected = merge_nodes(
+        merge_nodes(
+            HuffmanNode::new_leaf(b'a', 2),
+            merge_leaf_nodes((b'b', 1), (b'c', 1)),
+        ),
+        HuffmanNode::new_leaf(b'd', 5),
+    );
+    assert_eq!(tree, expected);
+}
@@ -1,6 +1,8 @@
+use crate::common::chain_tree;
 use std::io::Cursor;
 use tdd_huffman::input_bit_stream::InputBitStream;
-use tdd_huffman::tree_deserialization::deserialize_tree;
+use tdd_huffman::tree_deserialization::{deserialize_tree, MAX_TREE_DEPTH};
+use tdd_huffman::{serialize_tree_to_bits, HuffmanError, HuffmanNode, OutputBitStream};
 
 #[test]
 fn deserializes_single_leaf_tree_with_symbol_a() {
@@ -49,3 +51,54 @@ fn deserializes_two_leaf_tree_structure() {
     assert_eq!(right_child.symbol(), Some(b'B'));
     assert_eq!(right_child.frequency(), 1);
 }
+
+fn serialized(tree: &HuffmanNode) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut bit_stream = OutputBitStream::new(&mut output);
+    serialize_tree_to_bits(tree, &mut bit_stream).unwrap();
+    bit_stream.flush().unwrap();
+    output
+}
+
+#[test]
+fn accepts_tree_at_maximum_depth() {
+    let tree = chain_tree(MAX_TREE_DEPTH as u8);
+    let mut bit_stream = InputBitStream::new(Cursor::new(serialized(&tree)));
+
+    let result = deserialize_tree(&mut bit_stream).expect("Deserialization should succeed");
+
+    assert_eq!(result, tree);
+}
+
+#[test]
+fn rejects_tree_deeper_than_maximum_depth() {
+    let tree = chain_tree(MAX_TREE_DEPTH as u8 + 1);
+    let mut bit_stream = InputBitStream::new(Cursor::new(serialized(&tree)));
+
+    let error = deserialize_tree(&mut bit_stream).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::LimitExceeded(_)));
+}
+
+#[test]
+fn rejects_endless_run_of_internal_nodes_without_recursing_deeply() {
+    // A stream of '0' bits describes internal nodes forever; it must fail, not overflow the stack
+    let mut bit_stream = InputBitStream::new(Cursor::new(vec![0u8; 1 << 16]));
+
+    let error = deserialize_tree(&mut bit_stream).unwrap_err();
+
+    assert!(matches!(error, HuffmanError::LimitExceeded(_)));
+}
+
+#[test]
+fn rejects_tree_with_duplicate_symbols() {
+    // Arrange: bit stream 0 1{A} 1{A} = 0101000001101000001
+    // Packed into bytes: 01010000 01101000 00100000 = 0x50, 0x68, 0x20
+    let mut bit_stream = InputBitStream::new(Cursor::new(vec![0x50, 0x68, 0x20]));
+
+    // Act
+    let error = deserialize_tree(&mut bit_stream).unwrap_err();
+
+    // Assert
+    assert!(matches!(error, HuffmanError::CorruptTree(_)));
+}